@@ -1,14 +1,19 @@
 extern crate cryt;
 extern crate clap;
-extern crate regex;
 
-use clap::{App, Arg, SubCommand};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::fmt::Display;
+use std::fs;
 use std::io::{self, Read, Write};
-use regex::Regex;
+use std::process;
+use std::str::FromStr;
+use std::thread;
 
-use cryt::criteria::{self, BytesCriterion};
-use cryt::encoding;
-use cryt::xor::{self, Xor, KeysizeCriterion};
+use cryt::criteria::{self, Criterion, NgramModel};
+use cryt::encoding::{self, AutoDecoder, Base32, Base64, Alphabet, Chain, Encoding, Hexdump, Literal, Padding, Newline};
+use cryt::evaluation::{Evaluation, LengthResult};
+use cryt::pipeline::{Pipeline, Transform};
+use cryt::xor::{self, Charset, Xor, KeysizeCriterion};
 
 fn main() {
     let matches = App::new("cryt")
@@ -19,14 +24,167 @@ fn main() {
                                       .about("Encode input with the specified encoding")
                                       .subcommand(SubCommand::with_name("hex")
                                                   .about("Encode input in hex"))
+                                      .subcommand(SubCommand::with_name("base32")
+                                                  .about("Encode input in base32")
+                                                  .arg(Arg::with_name("hex")
+                                                       .short("x")
+                                                       .long("hex")
+                                                       .help("Use the extended hex alphabet (base32hex)"))
+                                                  .arg(Arg::with_name("no-padding")
+                                                       .short("n")
+                                                       .long("no-padding")
+                                                       .help("Omit the trailing '=' padding")))
+                                      .subcommand(SubCommand::with_name("base58")
+                                                  .about("Encode input in base58 (bitcoin alphabet)")
+                                                  .arg(Arg::with_name("check")
+                                                       .short("c")
+                                                       .long("check")
+                                                       .help("Append a Base58Check checksum")))
+                                      .subcommand(SubCommand::with_name("ascii85")
+                                                  .about("Encode input in ascii85")
+                                                  .arg(Arg::with_name("z85")
+                                                       .short("z")
+                                                       .long("z85")
+                                                       .conflicts_with("adobe")
+                                                       .help("Use the Z85 variant (input length must be a multiple of 4)"))
+                                                  .arg(Arg::with_name("adobe")
+                                                       .short("a")
+                                                       .long("adobe")
+                                                       .help("Enclose the result in <~ and ~>")))
+                                      .subcommand(SubCommand::with_name("hexdump")
+                                                  .about("Dump input with offsets, hex bytes and an ascii column, like xxd")
+                                                  .arg(Arg::with_name("canonical")
+                                                       .short("C")
+                                                       .long("canonical")
+                                                       .help("Use the hexdump -C layout"))
+                                                  .arg(Arg::with_name("width")
+                                                       .short("w")
+                                                       .long("width")
+                                                       .takes_value(true)
+                                                       .help("Number of bytes per line (default 16)"))
+                                                  .arg(Arg::with_name("group")
+                                                       .short("g")
+                                                       .long("group")
+                                                       .takes_value(true)
+                                                       .help("Number of bytes per group of hex digits (default 2, 1 with -C)"))
+                                                  .arg(Arg::with_name("uppercase")
+                                                       .short("u")
+                                                       .long("uppercase")
+                                                       .help("Use uppercase hex digits")))
+                                      .subcommand(SubCommand::with_name("literal")
+                                                  .about("Encode input as a source code literal")
+                                                  .arg(Arg::with_name("style")
+                                                       .short("s")
+                                                       .long("style")
+                                                       .takes_value(true)
+                                                       .required(true)
+                                                       .possible_values(&["c", "rust", "python", "escaped"])
+                                                       .help("C array, rust or python byte string, or backslash escaped text"))
+                                                  .arg(Arg::with_name("hex")
+                                                       .short("x")
+                                                       .long("hex")
+                                                       .help("Escape every byte as \\xNN, including printable ones")))
                                       .subcommand(SubCommand::with_name("base64")
-                                                  .about("Encode input in base64")))
+                                                  .about("Encode input in base64")
+                                                  .arg(Arg::with_name("url-safe")
+                                                       .short("u")
+                                                       .long("url-safe")
+                                                       .help("Use the URL and filename safe alphabet"))
+                                                  .arg(Arg::with_name("no-padding")
+                                                       .short("n")
+                                                       .long("no-padding")
+                                                       .help("Omit the trailing '=' padding"))
+                                                  .arg(Arg::with_name("wrap")
+                                                       .short("w")
+                                                       .long("wrap")
+                                                       .takes_value(true)
+                                                       .help("Wrap lines after the given number of symbols (0 disables wrapping)"))
+                                                  .arg(Arg::with_name("crlf")
+                                                       .long("crlf")
+                                                       .help("Separate wrapped lines with CRLF instead of LF"))
+                                                  .arg(Arg::with_name("mime")
+                                                       .short("m")
+                                                       .long("mime")
+                                                       .help("MIME encoding: lines of 76 symbols separated by CRLF"))))
                           .subcommand(SubCommand::with_name("decode")
                                       .about("Decode input with the specified encoding")
                                       .subcommand(SubCommand::with_name("hex")
                                                   .about("Decode input in hex"))
+                                      .subcommand(SubCommand::with_name("base32")
+                                                  .about("Decode input in base32")
+                                                  .arg(Arg::with_name("hex")
+                                                       .short("x")
+                                                       .long("hex")
+                                                       .help("Use the extended hex alphabet (base32hex)"))
+                                                  .arg(Arg::with_name("padding")
+                                                       .short("p")
+                                                       .long("padding")
+                                                       .takes_value(true)
+                                                       .possible_values(&["required", "optional", "omitted"])
+                                                       .help("Whether the input must, may or must not be padded with '='")))
+                                      .subcommand(SubCommand::with_name("base58")
+                                                  .about("Decode input in base58 (bitcoin alphabet)")
+                                                  .arg(Arg::with_name("check")
+                                                       .short("c")
+                                                       .long("check")
+                                                       .help("Verify and remove a Base58Check checksum")))
+                                      .subcommand(SubCommand::with_name("ascii85")
+                                                  .about("Decode input in ascii85")
+                                                  .arg(Arg::with_name("z85")
+                                                       .short("z")
+                                                       .long("z85")
+                                                       .help("Use the Z85 variant")))
+                                      .subcommand(SubCommand::with_name("hexdump")
+                                                  .about("Read back a dump made by xxd or hexdump -C"))
+                                      .subcommand(SubCommand::with_name("literal")
+                                                  .about("Decode a source code literal")
+                                                  .arg(Arg::with_name("style")
+                                                       .short("s")
+                                                       .long("style")
+                                                       .takes_value(true)
+                                                       .required(true)
+                                                       .possible_values(&["c", "rust", "python", "escaped"])
+                                                       .help("C array, rust or python byte string, or backslash escaped text")))
+                                      .subcommand(SubCommand::with_name("auto")
+                                                  .about("Detect and unwrap nested encodings")
+                                                  .arg(Arg::with_name("encodings")
+                                                       .short("e")
+                                                       .long("encodings")
+                                                       .takes_value(true)
+                                                       .use_delimiter(true)
+                                                       .possible_values(&["hex", "base32", "base32hex", "base64", "base64url", "base58", "ascii85", "z85", "hexdump",
+                                                                         "c", "rust", "python", "escaped"])
+                                                       .help("Comma separated encodings to try, in order of preference"))
+                                                  .arg(Arg::with_name("depth")
+                                                       .short("d")
+                                                       .long("depth")
+                                                       .takes_value(true)
+                                                       .help("Maximum number of layers to unwrap"))
+                                                  .arg(Arg::with_name("top")
+                                                       .short("t")
+                                                       .long("top")
+                                                       .takes_value(true)
+                                                       .help("Print the given number of best chains with their results")))
                                       .subcommand(SubCommand::with_name("base64")
-                                                  .about("Decode input in base64")))
+                                                  .about("Decode input in base64")
+                                                  .arg(Arg::with_name("url-safe")
+                                                       .short("u")
+                                                       .long("url-safe")
+                                                       .help("Use the URL and filename safe alphabet (padding becomes optional)"))
+                                                  .arg(Arg::with_name("padding")
+                                                       .short("p")
+                                                       .long("padding")
+                                                       .takes_value(true)
+                                                       .possible_values(&["required", "optional", "omitted"])
+                                                       .help("Whether the input must, may or must not be padded with '='"))
+                                                  .arg(Arg::with_name("lenient")
+                                                       .short("l")
+                                                       .long("lenient")
+                                                       .help("Skip whitespace and accept symbols of both alphabets"))
+                                                  .arg(Arg::with_name("mime")
+                                                       .short("m")
+                                                       .long("mime")
+                                                       .help("MIME decoding: lenient and accepting wrapped lines"))))
                           .subcommand(SubCommand::with_name("encrypt")
                                       .about("Encrypt input with the specified algorithm")
                                       .subcommand(SubCommand::with_name("xor")
@@ -47,6 +205,94 @@ fn main() {
                                                        .takes_value(true)
                                                        .required(true)
                                                        .help("xor key to be used"))))
+                          .subcommand(SubCommand::with_name("pipe")
+                                      .about("Apply a pipeline of transformations, e.g. 'b64d | xor -k ICE | hex'")
+                                      .arg(Arg::with_name("pipeline")
+                                           .required(true)
+                                           .help("Stages separated by '|': an encoding name encodes, the name followed by 'd' decodes, 'xor -k KEY' or 'xor -x HEXKEY' xors")))
+                          .subcommand(SubCommand::with_name("ngram")
+                                      .about("Build n-gram language models for the ngram:PATH criterion")
+                                      .subcommand(SubCommand::with_name("train")
+                                                  .about("Train a model on corpus files, or on the input if none are given")
+                                                  .arg(Arg::with_name("order")
+                                                       .short("n")
+                                                       .long("order")
+                                                       .takes_value(true)
+                                                       .help("Length of the n-grams, from 1 to 4 (default 4)"))
+                                                  .arg(Arg::with_name("output")
+                                                       .short("o")
+                                                       .long("output")
+                                                       .takes_value(true)
+                                                       .required(true)
+                                                       .help("Path of the model file to write"))
+                                                  .arg(Arg::with_name("corpus")
+                                                       .multiple(true)
+                                                       .help("Text files to train on")))
+                                      .subcommand(SubCommand::with_name("score")
+                                                  .about("Print the score of the input under a model")
+                                                  .arg(Arg::with_name("model")
+                                                       .short("m")
+                                                       .long("model")
+                                                       .takes_value(true)
+                                                       .required(true)
+                                                       .help("Path of the model file"))))
+                          .subcommand(SubCommand::with_name("analyze")
+                                      .about("Print the byte histogram, entropy profile and index of coincidence of the input")
+                                      .arg(Arg::with_name("window")
+                                           .short("w")
+                                           .long("window")
+                                           .takes_value(true)
+                                           .help("Size of the windows of the entropy profile (default 256)"))
+                                      .arg(Arg::with_name("step")
+                                           .short("s")
+                                           .long("step")
+                                           .takes_value(true)
+                                           .help("Distance between the windows of the entropy profile (default the window size)"))
+                                      .arg(Arg::with_name("top")
+                                           .short("t")
+                                           .long("top")
+                                           .takes_value(true)
+                                           .help("Number of most frequent bytes in the histogram (default 16)")))
+                          .subcommand(SubCommand::with_name("criteria")
+                                      .about("List the criteria that can be combined in criterion expressions"))
+                          .subcommand(SubCommand::with_name("evaluate")
+                                      .about("Measure how often xor attacks recover plaintexts of a corpus with each criterion")
+                                      .arg(Arg::with_name("criterion")
+                                           .short("c")
+                                           .long("criterion")
+                                           .takes_value(true)
+                                           .multiple(true)
+                                           .number_of_values(1)
+                                           .help("Criterion expression to evaluate, may be repeated (default printable, text and english)"))
+                                      .arg(Arg::with_name("attack")
+                                           .short("a")
+                                           .long("attack")
+                                           .takes_value(true)
+                                           .possible_values(&["single", "repeated"])
+                                           .help("Attack to evaluate (default both)"))
+                                      .arg(Arg::with_name("lengths")
+                                           .short("l")
+                                           .long("lengths")
+                                           .takes_value(true)
+                                           .help("Comma separated plaintext lengths (default 16,32,64,128,256)"))
+                                      .arg(Arg::with_name("trials")
+                                           .short("n")
+                                           .long("trials")
+                                           .takes_value(true)
+                                           .help("Number of plaintexts of each length (default 20)"))
+                                      .arg(Arg::with_name("max-keysize")
+                                           .short("k")
+                                           .long("max-keysize")
+                                           .takes_value(true)
+                                           .help("Longest key of the repeated xor trials, which use keys from 2 bytes (default 8)"))
+                                      .arg(Arg::with_name("seed")
+                                           .short("s")
+                                           .long("seed")
+                                           .takes_value(true)
+                                           .help("Seed of the random plaintext offsets and keys (default 1)"))
+                                      .arg(Arg::with_name("corpus")
+                                           .multiple(true)
+                                           .help("Text files to take plaintexts from, or the input if none are given")))
                           .subcommand(SubCommand::with_name("attack")
                                       .about("Attack the specified encryption algorithm to decrypt the input")
                                       .subcommand(SubCommand::with_name("xor")
@@ -55,13 +301,89 @@ fn main() {
                                                        .short("c")
                                                        .long("criterion")
                                                        .takes_value(true)
-                                                       //.possible_values(&["printable", "text"])
-                                                       .help("criterion to be used for scoring the results"))
+                                                       .help("Criterion expression for scoring the results, e.g. '0.7*english + 0.3*byte(32)' (see cryt criteria)"))
                                                   .arg(Arg::with_name("detailed")
                                                        .short("d")
                                                        .long("detailed")
                                                        .required(false)
                                                        .help("Print decrypted result, the key and the score for the selected criterion"))
+                                                  .arg(Arg::with_name("top")
+                                                       .short("t")
+                                                       .long("top")
+                                                       .takes_value(true)
+                                                       .help("Print the N best keys with their score and result, from the best (equal scores rank the lower key first)"))
+                                                  .arg(Arg::with_name("threads")
+                                                       .short("j")
+                                                       .long("threads")
+                                                       .takes_value(true)
+                                                       .help("Number of threads the attacks run on, results do not depend on it (default one per core)"))
+                                                  .arg(Arg::with_name("charset")
+                                                       .long("charset")
+                                                       .takes_value(true)
+                                                       .possible_values(&["all", "printable", "alphanumeric", "hex"])
+                                                       .help("Bytes the key is made of (default all)"))
+                                                  .arg(Arg::with_name("key-bytes")
+                                                       .long("key-bytes")
+                                                       .takes_value(true)
+                                                       .conflicts_with("charset")
+                                                       .help("Bytes the key is made of, which may contain escapes like \\x04"))
+                                                  .subcommand(SubCommand::with_name("crib")
+                                                              .about("Interactively drag cribs across messages encrypted with the same keystream")
+                                                              .arg(Arg::with_name("encoding")
+                                                                   .short("e")
+                                                                   .long("encoding")
+                                                                   .takes_value(true)
+                                                                   .help("Encoding of the ciphertexts (default hex, see cryt decode auto)"))
+                                                              .arg(Arg::with_name("top")
+                                                                   .short("t")
+                                                                   .long("top")
+                                                                   .takes_value(true)
+                                                                   .help("Number of best positions printed for each crib (default 10)"))
+                                                              .arg(Arg::with_name("ciphertexts")
+                                                                   .multiple(true)
+                                                                   .required(true)
+                                                                   .min_values(2)
+                                                                   .help("Two or more ciphertexts")))
+                                                  .subcommand(SubCommand::with_name("known")
+                                                              .about("Recover a repeated xor key from a known part of the plaintext")
+                                                              .arg(Arg::with_name("crib")
+                                                                   .long("crib")
+                                                                   .takes_value(true)
+                                                                   .required(true)
+                                                                   .help("Known plaintext, which may contain escapes like \\x04"))
+                                                              .arg(Arg::with_name("offset")
+                                                                   .short("o")
+                                                                   .long("offset")
+                                                                   .takes_value(true)
+                                                                   .help("Position of the known plaintext (default 0)"))
+                                                              .arg(Arg::with_name("period")
+                                                                   .short("p")
+                                                                   .long("period")
+                                                                   .takes_value(true)
+                                                                   .help("Length of the key, by default the smallest period of the recovered key bytes")))
+                                                  .subcommand(SubCommand::with_name("many-time")
+                                                              .about("Recover the keystream shared by many messages, one per line, e.g. a reused one time pad")
+                                                              .arg(Arg::with_name("encoding")
+                                                                   .short("e")
+                                                                   .long("encoding")
+                                                                   .takes_value(true)
+                                                                   .help("Encoding of each line, or 'raw' to take lines as they are (default hex, see cryt decode auto)"))
+                                                              .arg(Arg::with_name("input")
+                                                                   .help("File to read the lines from, or the input if not given")))
+                                                  .subcommand(SubCommand::with_name("detect")
+                                                              .about("Find the lines encrypted with single byte xor among many, e.g. a file of hex ciphertexts")
+                                                              .arg(Arg::with_name("encoding")
+                                                                   .short("e")
+                                                                   .long("encoding")
+                                                                   .takes_value(true)
+                                                                   .help("Encoding of each line, or 'raw' to take lines as they are (default hex, see cryt decode auto)"))
+                                                              .arg(Arg::with_name("top")
+                                                                   .short("t")
+                                                                   .long("top")
+                                                                   .takes_value(true)
+                                                                   .help("Number of best lines to print (default 5)"))
+                                                              .arg(Arg::with_name("input")
+                                                                   .help("File to read the lines from, or the input if not given")))
                                                   .subcommand(SubCommand::with_name("keysize")
                                                               .about("Determine the keysize of a repeated xor encryption")
                                                               .arg(Arg::with_name("criterion")
@@ -69,7 +391,7 @@ fn main() {
                                                                    .short("c")
                                                                    .long("criterion")
                                                                    .takes_value(true)
-                                                                   .possible_values(&["hamming-distance"])
+                                                                   .possible_values(&["hamming-distance", "kasiski", "ic", "autocorrelation"])
                                                                    .required(false))
                                                               .arg(Arg::with_name("min")
                                                                    .help("Minimum keysize to try")
@@ -89,26 +411,37 @@ fn main() {
                                                                    .short("k")
                                                                    .long("keysize-criterion")
                                                                    .takes_value(true)
-                                                                   .possible_values(&["hamming-distance"])
+                                                                   .possible_values(&["hamming-distance", "kasiski", "ic", "autocorrelation"])
                                                                    .required(false))
                                                               .arg(Arg::with_name("xor-criterion")
                                                                    .short("x")
                                                                    .long("xor-criterion")
                                                                    .takes_value(true)
-                                                                   //.possible_values(&["printable", "text"])
-                                                                   .help("criterion to be used for scoring the intermediate block results"))
+                                                                   .help("Criterion expression for scoring the intermediate block results"))
                                                               .arg(Arg::with_name("criterion")
                                                                    .short("c")
                                                                    .long("criterion")
                                                                    .takes_value(true)
-                                                                   //.possible_values(&["printable", "text"])
-                                                                   .help("criterion to be used for scoring the results for different keysizes"))
+                                                                   .help("Criterion expression for scoring the results for different keysizes"))
                                                               .arg(Arg::with_name("keysizes-try")
                                                                    .short("t")
                                                                    .long("keysizes-try")
                                                                    .takes_value(true)
                                                                    .required(false)
                                                                    .help("criterion to be used for scoring the results for different keysizes"))
+                                                              .arg(Arg::with_name("detailed")
+                                                                   .short("d")
+                                                                   .long("detailed")
+                                                                   .help("Print the keysize, the score, the margin of each key byte over the second best and the key found for each keysize tried"))
+                                                              .arg(Arg::with_name("refine")
+                                                                   .short("r")
+                                                                   .long("refine")
+                                                                   .help("Refine the key byte by byte against the whole decrypted result, and print the changed bytes"))
+                                                              .arg(Arg::with_name("refine-criterion")
+                                                                   .long("refine-criterion")
+                                                                   .takes_value(true)
+                                                                   .requires("refine")
+                                                                   .help("Criterion expression for scoring the refined results (default words, letter frequencies and text bytes)"))
                                                               .arg(Arg::with_name("min")
                                                                    .help("Minimum keysize to try")
                                                                    .long("min")
@@ -124,16 +457,128 @@ fn main() {
 
 
     if let Some(matches) = matches.subcommand_matches("encode") {
-        if let Some(_) = matches.subcommand_matches("base64") {
-            run_encode_base64();
+        if let Some(base64_matches) = matches.subcommand_matches("base64") {
+            let mut engine = if base64_matches.is_present("mime") { Base64::mime() } else { Base64::new() };
+
+            if base64_matches.is_present("url-safe") {
+                engine = engine.with_alphabet(Alphabet::UrlSafe);
+            }
+
+            if base64_matches.is_present("no-padding") {
+                engine = engine.with_padding(Padding::Omitted);
+            }
+
+            if let Some(v) = base64_matches.value_of("wrap") {
+                engine = engine.with_line_width(Some(parse_or_exit::<usize>(v)));
+            }
+
+            if base64_matches.is_present("crlf") {
+                engine = engine.with_newline(Newline::CrLf);
+            }
+
+            run_encode_base64(engine);
         } else if let Some(_) = matches.subcommand_matches("hex") {
             run_encode_hex();
+        } else if let Some(base32_matches) = matches.subcommand_matches("base32") {
+            let mut engine = Base32::new().with_hex_alphabet(base32_matches.is_present("hex"));
+            if base32_matches.is_present("no-padding") {
+                engine = engine.with_padding(Padding::Omitted);
+            }
+
+            run_encode_buffered(|input| Ok(engine.encode(input)));
+        } else if let Some(base58_matches) = matches.subcommand_matches("base58") {
+            if base58_matches.is_present("check") {
+                run_encode_buffered(|input| Ok(encoding::base58check_encode(input)));
+            } else {
+                run_encode_buffered(|input| Ok(encoding::base58_encode(input)));
+            }
+        } else if let Some(ascii85_matches) = matches.subcommand_matches("ascii85") {
+            if ascii85_matches.is_present("z85") {
+                run_encode_buffered(encoding::z85_encode);
+            } else if ascii85_matches.is_present("adobe") {
+                run_encode_buffered(|input| Ok(format!("<~{}~>", encoding::ascii85_encode(input))));
+            } else {
+                run_encode_buffered(|input| Ok(encoding::ascii85_encode(input)));
+            }
+        } else if let Some(hexdump_matches) = matches.subcommand_matches("hexdump") {
+            let mut engine = if hexdump_matches.is_present("canonical") { Hexdump::canonical() } else { Hexdump::new() };
+
+            if let Some(v) = hexdump_matches.value_of("width") {
+                engine = engine.with_width(parse_or_exit::<usize>(v));
+            }
+
+            if let Some(v) = hexdump_matches.value_of("group") {
+                engine = engine.with_group(parse_or_exit::<usize>(v));
+            }
+
+            engine = engine.with_uppercase(hexdump_matches.is_present("uppercase"));
+
+            run_encode_buffered(|input| Ok(engine.encode(input)));
+        } else if let Some(literal_matches) = matches.subcommand_matches("literal") {
+            let engine = literal_engine(literal_matches)
+                .with_escape_all(literal_matches.is_present("hex"));
+
+            run_encode_buffered(|input| Ok(engine.encode(input)));
         }
     } else if let Some(matches) = matches.subcommand_matches("decode") {
-        if let Some(_) = matches.subcommand_matches("base64") {
-            run_decode_base64();
+        if let Some(base64_matches) = matches.subcommand_matches("base64") {
+            let mut engine = if base64_matches.is_present("mime") { Base64::mime() } else { Base64::new() };
+
+            if base64_matches.is_present("url-safe") {
+                engine = engine
+                    .with_alphabet(Alphabet::UrlSafe)
+                    .with_padding(Padding::Optional);
+            }
+
+            if let Some(padding) = padding_value(base64_matches) {
+                engine = engine.with_padding(padding);
+            }
+
+            if base64_matches.is_present("lenient") {
+                engine = engine.with_lenient(true);
+            }
+
+            run_decode_base64(engine);
         } else if let Some(_) = matches.subcommand_matches("hex") {
             run_decode_hex();
+        } else if let Some(base32_matches) = matches.subcommand_matches("base32") {
+            let mut engine = Base32::new().with_hex_alphabet(base32_matches.is_present("hex"));
+            if let Some(padding) = padding_value(base32_matches) {
+                engine = engine.with_padding(padding);
+            }
+
+            run_decode_buffered(|input| engine.decode(input));
+        } else if let Some(base58_matches) = matches.subcommand_matches("base58") {
+            if base58_matches.is_present("check") {
+                run_decode_buffered(encoding::base58check_decode);
+            } else {
+                run_decode_buffered(encoding::base58_decode);
+            }
+        } else if let Some(ascii85_matches) = matches.subcommand_matches("ascii85") {
+            if ascii85_matches.is_present("z85") {
+                run_decode_buffered(encoding::z85_decode);
+            } else {
+                run_decode_buffered(encoding::ascii85_decode);
+            }
+        } else if let Some(_) = matches.subcommand_matches("hexdump") {
+            run_decode_buffered(encoding::hexdump_decode);
+        } else if let Some(literal_matches) = matches.subcommand_matches("literal") {
+            let engine = literal_engine(literal_matches);
+            run_decode_buffered(|input| engine.decode(input));
+        } else if let Some(auto_matches) = matches.subcommand_matches("auto") {
+            let mut decoder = AutoDecoder::new();
+
+            if let Some(names) = auto_matches.values_of("encodings") {
+                decoder = decoder.with_encodings(names.filter_map(Encoding::from_name).collect());
+            }
+
+            if let Some(v) = auto_matches.value_of("depth") {
+                decoder = decoder.with_max_depth(parse_or_exit::<usize>(v));
+            }
+
+            let top = auto_matches.value_of("top").map(parse_or_exit::<usize>);
+
+            run_decode_auto(decoder, top);
         }
     } else if let Some(matches) = matches.subcommand_matches("encrypt") {
         if let Some(matches) = matches.subcommand_matches("xor") {
@@ -157,14 +602,98 @@ fn main() {
                 }
             }
         }
+    } else if let Some(matches) = matches.subcommand_matches("pipe") {
+        match Pipeline::parse(matches.value_of("pipeline").unwrap()) {
+            Ok(pipeline) => run_pipe(pipeline),
+            Err(error) => exit_with_error(error),
+        }
+    } else if let Some(matches) = matches.subcommand_matches("ngram") {
+        if let Some(train_matches) = matches.subcommand_matches("train") {
+            let order = train_matches.value_of("order").map_or(4, parse_or_exit::<usize>);
+            let corpus: Vec<&str> = train_matches.values_of("corpus").map_or(Vec::new(), |v| v.collect());
+
+            run_ngram_train(order, train_matches.value_of("output").unwrap(), &corpus);
+        } else if let Some(score_matches) = matches.subcommand_matches("score") {
+            run_ngram_score(load_ngram_model(score_matches.value_of("model").unwrap()));
+        }
+    } else if let Some(analyze_matches) = matches.subcommand_matches("analyze") {
+        let window = analyze_matches.value_of("window").map_or(256, parse_or_exit::<usize>);
+        let step = analyze_matches.value_of("step").map_or(window, parse_or_exit::<usize>);
+        let top = analyze_matches.value_of("top").map_or(16, parse_or_exit::<usize>);
+
+        run_analyze(window, step, top);
+    } else if let Some(_) = matches.subcommand_matches("criteria") {
+        run_list_criteria();
+    } else if let Some(evaluate_matches) = matches.subcommand_matches("evaluate") {
+        let criteria: Vec<&str> = evaluate_matches.values_of("criterion").map_or(vec!["printable", "text", "english"], |v| v.collect());
+        let lengths: Vec<usize> = evaluate_matches.value_of("lengths").map_or(vec![16, 32, 64, 128, 256], |v| {
+            v.split(',').map(|l| parse_or_exit::<usize>(l.trim())).collect()
+        });
+        let trials = evaluate_matches.value_of("trials").map_or(20, parse_or_exit::<usize>);
+        let max_keysize = evaluate_matches.value_of("max-keysize").map_or(8, parse_or_exit::<usize>);
+        // Repeated xor keys are at least 2 bytes long
+        if max_keysize < 2 {
+            exit_with_error("--max-keysize must be at least 2");
+        }
+        let seed = evaluate_matches.value_of("seed").map_or(1, parse_or_exit::<u64>);
+        let corpus: Vec<&str> = evaluate_matches.values_of("corpus").map_or(Vec::new(), |v| v.collect());
+
+        let evaluation = Evaluation::new(&read_corpus(&corpus))
+            .with_lengths(&lengths)
+            .with_trials(trials)
+            .with_keysizes(2, max_keysize)
+            .with_seed(seed);
+
+        match evaluate_matches.value_of("attack") {
+            Some("single") => run_evaluate_single_byte(&evaluation, &criteria),
+            Some(_) => run_evaluate_repeated(&evaluation, &criteria, max_keysize),
+            None => {
+                run_evaluate_single_byte(&evaluation, &criteria);
+                println!("");
+                run_evaluate_repeated(&evaluation, &criteria, max_keysize);
+            }
+        }
     } else if let Some(matches) = matches.subcommand_matches("attack") {
         if let Some(xor_matches) = matches.subcommand_matches("xor") {
-            if let Some(keysize_matches) = xor_matches.subcommand_matches("keysize") {
-                let criterion = match keysize_matches.value_of("criterion") {
-                    Some("hamming-distance") => Box::new(xor::hamming_distance_criterion),
-                    Some(_) => Box::new(xor::hamming_distance_criterion),
-                    None => Box::new(xor::hamming_distance_criterion),
-                };
+            if let Some(crib_matches) = xor_matches.subcommand_matches("crib") {
+                let criterion = criterion_or_exit(xor_matches, "criterion", "text");
+                let encoding = crib_matches.value_of("encoding").map_or(Encoding::Hex, |name| {
+                    Encoding::from_name(name).unwrap_or_else(|| exit_with_error(format!("unknown encoding '{}'", name)))
+                });
+                let top = crib_matches.value_of("top").map_or(10, parse_or_exit::<usize>);
+
+                let ciphertexts: Vec<Vec<u8>> = crib_matches.values_of("ciphertexts").unwrap()
+                    .map(|c| encoding.decode(c.as_bytes()).unwrap_or_else(|error| exit_with_error(format!("'{}': {}", c, error))))
+                    .collect();
+
+                run_attack_xor_crib(xor::CribDrag::new(&ciphertexts).with_criterion(criterion), top);
+                return;
+            } else if let Some(known_matches) = xor_matches.subcommand_matches("known") {
+                let crib = known_matches.value_of("crib").unwrap();
+                let crib = Literal::new().decode(crib).unwrap_or_else(|error| exit_with_error(format!("invalid crib '{}': {}", crib, error)));
+                let mut attack = xor::KnownPlaintextAttack::new(&crib)
+                    .with_offset(known_matches.value_of("offset").map_or(0, parse_or_exit::<usize>));
+                if let Some(period) = known_matches.value_of("period") {
+                    attack = attack.with_period(parse_or_exit::<usize>(period));
+                }
+
+                run_attack_xor_known(attack);
+                return;
+            } else if let Some(many_time_matches) = xor_matches.subcommand_matches("many-time") {
+                let attack = single_byte_attack(xor_matches, "english + text");
+                let encoding = line_encoding(many_time_matches);
+
+                run_attack_xor_many_time(attack, encoding, many_time_matches.value_of("input"));
+                return;
+            } else if let Some(detect_matches) = xor_matches.subcommand_matches("detect") {
+                let attack = single_byte_attack(xor_matches, "english");
+                let encoding = line_encoding(detect_matches);
+                let top = detect_matches.value_of("top").map_or(5, parse_or_exit::<usize>);
+
+                run_attack_xor_detect(attack, encoding, top, detect_matches.value_of("input"));
+                return;
+            } else if let Some(keysize_matches) = xor_matches.subcommand_matches("keysize") {
+                let criterion = keysize_criterion(keysize_matches.value_of("criterion"));
 
                 let min = match keysize_matches.value_of("min") {
                     Some(v) => v.parse::<u32>().unwrap(),
@@ -176,49 +705,19 @@ fn main() {
                     None => 1
                 };
 
-                run_attack_xor_keysize(criterion, min, max);
+                run_attack_xor_keysize(criterion, min, max, threads(xor_matches));
                 return;
             } else if let Some(repeated_matches) = xor_matches.subcommand_matches("repeated") {
-                let xor_criterion: Box<BytesCriterion> = match repeated_matches.value_of("xor-criterion") {
-                    Some("printable") => Box::new(criteria::printable_bytes),
-                    Some("text") => Box::new(criteria::text_bytes),
-                    Some(value) => {
-                        let re = Regex::new(r"byte\((\d{1,3})\)").unwrap();
-                        let byte_text = re.captures(value).unwrap().get(1).unwrap().as_str();
-                        let byte = byte_text.parse::<u8>().unwrap();
-
-                        if value.contains("byte(") {
-                            criteria::make_common_byte(byte)
-                        } else {
-                            Box::new(criteria::text_bytes)
-                        }
-                    },
-                    None => Box::new(criteria::text_bytes)
-                };
+                let xor_criterion = criterion_or_exit(repeated_matches, "xor-criterion", "text");
 
-                let result_criterion: Box<BytesCriterion> = match xor_matches.value_of("criterion") {
-                    Some("printable") => Box::new(criteria::printable_bytes),
-                    Some("text") => Box::new(criteria::text_bytes),
-                    Some(value) => {
-                        let re = Regex::new(r"byte\((\d{1,3})\)").unwrap();
-                        let byte_text = re.captures(value).unwrap().get(1).unwrap().as_str();
-                        let byte = byte_text.parse::<u8>().unwrap();
-
-                        if value.contains("byte(") {
-                            criteria::make_common_byte(byte)
-                        } else {
-                            Box::new(criteria::text_bytes)
-                        }
-                    },
-                    None => Box::new(criteria::text_bytes),
+                // The criterion of repeated, or else the one given to attack xor
+                let result_criterion = match repeated_matches.value_of("criterion") {
+                    Some(expression) => parse_criterion_or_exit(expression),
+                    None => criterion_or_exit(xor_matches, "criterion", "text"),
                 };
 
 
-                let keysize_criterion = match repeated_matches.value_of("keysize-criterion") {
-                    Some("hamming-distance") => Box::new(xor::hamming_distance_criterion),
-                    Some(_) => Box::new(xor::hamming_distance_criterion),
-                    None => Box::new(xor::hamming_distance_criterion),
-                };
+                let keysize_criterion = keysize_criterion(repeated_matches.value_of("keysize-criterion"));
 
                 let keysize_try = match repeated_matches.value_of("keysizes-try") {
                     Some(v) => v.parse::<usize>().unwrap(),
@@ -235,31 +734,43 @@ fn main() {
                     None => 1
                 };
 
-                run_attack_xor_repeated(keysize_criterion, min, max, keysize_try, xor_criterion, result_criterion);
+                let attack = xor::RepeatedAttack::new()
+                    .with_single_byte_attack(xor::SingleByteAttack::new()
+                                             .with_criterion(xor_criterion)
+                                             .with_charset(charset(xor_matches)))
+                    .with_keysize_attack(xor::KeysizeAttack::new()
+                                         .with_min_length(min)
+                                         .with_max_length(max)
+                                         .with_criterion(keysize_criterion)
+                                         .with_threads(threads(xor_matches)))
+                    .with_result_criterion(result_criterion)
+                    .with_keysizes_count(keysize_try)
+                    .with_threads(threads(xor_matches));
+
+                let refinement = if repeated_matches.is_present("refine") {
+                    let mut refinement = xor::KeyRefinement::new()
+                        .with_charset(charset(xor_matches))
+                        .with_threads(threads(xor_matches));
+                    if let Some(expression) = repeated_matches.value_of("refine-criterion") {
+                        refinement = refinement.with_criterion(parse_criterion_or_exit(expression));
+                    }
+                    Some(refinement)
+                } else {
+                    None
+                };
+
+                run_attack_xor_repeated(attack, refinement, repeated_matches.is_present("detailed"));
                 return;
             }
 
-            let criterion: Box<BytesCriterion> = match xor_matches.value_of("criterion") {
-                Some("printable") => Box::new(criteria::printable_bytes),
-                Some("text") => Box::new(criteria::text_bytes),
-                Some(value) => {
-                    let re = Regex::new(r"byte\((\d{1,3})\)").unwrap();
-                    let byte_text = re.captures(value).unwrap().get(1).unwrap().as_str();
-                    let byte = byte_text.parse::<u8>().unwrap();
-
-                    if value.contains("byte(") {
-                        criteria::make_common_byte(byte)
-                    } else {
-                        Box::new(criteria::text_bytes)
-                    }
-                },
-                None => Box::new(criteria::printable_bytes)
-            };
+            let attack = single_byte_attack(xor_matches, "printable");
 
-            if xor_matches.is_present("detailed") {
-                run_attack_xor_detailed(criterion);
+            if let Some(top) = xor_matches.value_of("top") {
+                run_attack_xor_top(attack, parse_or_exit::<usize>(top));
+            } else if xor_matches.is_present("detailed") {
+                run_attack_xor_detailed(attack);
             } else {
-                run_attack_xor(criterion);
+                run_attack_xor(attack);
             }
         }
     } else {
@@ -267,41 +778,95 @@ fn main() {
     }
 }
 
-fn run_encode_base64() {
+fn run_encode_base64(engine: Base64) {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut encoder = encoding::Base64Encoder::new(stdout.lock())
+        .with_engine(engine);
+
+    if let Err(error) = io::copy(&mut stdin.lock(), &mut encoder).and_then(|_| encoder.finish()) {
+        exit_with_error(error);
+    }
+}
+
+fn run_encode_hex() {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut encoder = encoding::HexEncoder::new(stdout.lock());
+
+    if let Err(error) = io::copy(&mut stdin.lock(), &mut encoder) {
+        exit_with_error(error);
+    }
+}
+
+fn run_decode_base64(engine: Base64) {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut decoder = encoding::Base64Decoder::new(stdin.lock())
+        .with_engine(engine);
+
+    if let Err(error) = io::copy(&mut decoder, &mut stdout.lock()) {
+        exit_with_error(error);
+    }
+}
+
+fn run_decode_hex() {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut decoder = encoding::HexDecoder::new(stdin.lock());
+
+    if let Err(error) = io::copy(&mut decoder, &mut stdout.lock()) {
+        exit_with_error(error);
+    }
+}
+
+fn run_encode_buffered<F: Fn(&[u8]) -> cryt::Result<String>>(encode: F) {
     let mut input = Vec::new();
     io::stdin().read_to_end(&mut input).unwrap();
 
-    let result = encoding::base64_encode(&input);
-    print!("{}", result);
+    match encode(&input) {
+        Ok(result) => print!("{}", result),
+        Err(error) => exit_with_error(error),
+    }
 }
 
-fn run_encode_hex() {
+fn run_decode_buffered<F: Fn(&[u8]) -> cryt::Result<Vec<u8>>>(decode: F) {
     let mut input = Vec::new();
     io::stdin().read_to_end(&mut input).unwrap();
 
-    let result = encoding::hex_encode(&input);
-    print!("{}", result);
+    match decode(&input) {
+        Ok(result) => io::stdout().write_all(&result).unwrap(),
+        Err(error) => exit_with_error(error),
+    }
 }
 
-fn run_decode_base64() {
-    let mut input = String::new();
-    match io::stdin().read_to_string(&mut input) {
-        Ok(_) => {
-            let result = encoding::base64_decode(&input);
-            io::stdout().write(&result).unwrap();
+fn run_decode_auto(decoder: AutoDecoder, top: Option<usize>) {
+    let mut input = Vec::new();
+    io::stdin().read_to_end(&mut input).unwrap();
+
+    let chains = decoder.result(&input);
+    let mut stdout = io::stdout();
+
+    match top {
+        Some(count) => {
+            for chain in chains.iter().take(count) {
+                print!("Chain: {}\tScore: {}\tResult: ", chain_description(chain), chain.score);
+                stdout.write_all(&chain.payload).unwrap();
+                println!();
+            }
+        }
+        None => {
+            eprintln!("Chain: {}", chain_description(&chains[0]));
+            stdout.write_all(&chains[0].payload).unwrap();
         }
-        Err(error) => { println!("Error: {}", error); }
     }
 }
 
-fn run_decode_hex() {
-    let mut input = String::new();
-    match io::stdin().read_to_string(&mut input) {
-        Ok(_) => {
-            let result = encoding::hex_decode(&input);
-            io::stdout().write(&result).unwrap();
-        }
-        Err(error) => { println!("Error: {}", error); }
+fn chain_description(chain: &Chain) -> String {
+    if chain.encodings.is_empty() {
+        String::from("(none)")
+    } else {
+        chain.names().join(" -> ")
     }
 }
 
@@ -321,31 +886,284 @@ fn run_decrypt_xor(key: &str) {
     io::stdout().write(&result).unwrap();
 }
 
-fn run_attack_xor(criterion: Box<BytesCriterion>) {
+fn run_pipe(pipeline: Pipeline) {
     let mut input = Vec::new();
     io::stdin().read_to_end(&mut input).unwrap();
 
-    let (_, _, decrypted) = xor::SingleByteAttack::new()
-        .with_criterion(criterion)
-        .result(&input);
+    match pipeline.apply(&input) {
+        Ok(result) => io::stdout().write_all(&result).unwrap(),
+        Err(error) => exit_with_error(error),
+    }
+}
 
-    io::stdout().write(&decrypted).unwrap();
+// The contents of the given files, or the input if there are none
+fn read_corpus(corpus: &[&str]) -> Vec<u8> {
+    let mut input = Vec::new();
+    if corpus.is_empty() {
+        io::stdin().read_to_end(&mut input).unwrap();
+    }
+
+    for path in corpus {
+        match fs::read(path) {
+            Ok(text) => {
+                input.extend_from_slice(&text);
+                input.push(b'\n');
+            }
+            Err(error) => exit_with_error(format!("{}: {}", path, error)),
+        }
+    }
+
+    input
 }
 
-fn run_attack_xor_detailed(criterion: Box<BytesCriterion>) {
+fn run_ngram_train(order: usize, output: &str, corpus: &[&str]) {
+    let input = read_corpus(corpus);
+    let result = NgramModel::train(&input, order).and_then(|model| model.save(output));
+    if let Err(error) = result {
+        exit_with_error(error);
+    }
+}
+
+fn run_ngram_score(model: NgramModel) {
     let mut input = Vec::new();
     io::stdin().read_to_end(&mut input).unwrap();
 
-    let (key, score, decrypted) = xor::SingleByteAttack::new()
-        .with_criterion(criterion)
-        .result(&input);
+    println!("{}", model.score(&input));
+}
 
-    print!("Key: {}\tScore: {}\tResult: ", key, score);
-    io::stdout().write(&decrypted).unwrap();
+fn load_ngram_model(path: &str) -> NgramModel {
+    match NgramModel::load(path) {
+        Ok(model) => model,
+        Err(error) => exit_with_error(format!("{}: {}", path, error)),
+    }
+}
+
+fn run_attack_xor(attack: xor::SingleByteAttack) {
+    let mut input = Vec::new();
+    io::stdin().read_to_end(&mut input).unwrap();
+
+    let result = attack.result(&input);
+
+    io::stdout().write_all(&result.plaintext).unwrap();
+}
+
+fn run_attack_xor_detailed(attack: xor::SingleByteAttack) {
+    let mut input = Vec::new();
+    io::stdin().read_to_end(&mut input).unwrap();
+
+    let result = attack.result(&input);
+
+    print!("Key: {}\tScore: {}\tMargin: {}\tResult: ", result.key, result.score, result.margin);
+    io::stdout().write_all(&result.plaintext).unwrap();
     println!("");
 }
 
-fn run_attack_xor_keysize(criterion: Box<KeysizeCriterion>, min: u32, max: u32) {
+fn run_attack_xor_top(attack: xor::SingleByteAttack, top: usize) {
+    let mut input = Vec::new();
+    io::stdin().read_to_end(&mut input).unwrap();
+
+    let candidates = attack.candidates(&input);
+
+    let mut stdout = io::stdout();
+    for (key, score, decrypted) in candidates.into_iter().take(top) {
+        print!("Key: {}\tScore: {}\tResult: ", key, score);
+        stdout.write_all(&decrypted).unwrap();
+        println!("");
+    }
+}
+
+const CRIB_HELP: &'static str = "\
+drag TEXT               slide TEXT across every message and print the best positions
+drag -m M TEXT          slide TEXT across message M only
+lock M OFFSET TEXT      take TEXT as the plaintext of message M at OFFSET
+unlock OFFSET LENGTH    forget the keystream of LENGTH bytes at OFFSET
+show                    print the messages, '_' where the keystream is unknown
+key                     print the keystream in hex, '..' where it is unknown
+help                    print this help
+quit                    exit
+
+Messages are numbered from 1 and offsets from 0, TEXT may contain escapes like \\n or \\x00.";
+
+// Reads crib dragging commands from the input until it ends or quit
+fn run_attack_xor_crib(mut drag: xor::CribDrag, top: usize) {
+    let literal = Literal::new();
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+
+    println!("{} messages, type help for the commands", drag.messages());
+    loop {
+        print!("crib> ");
+        stdout.flush().unwrap();
+
+        let mut line = String::new();
+        if stdin.read_line(&mut line).unwrap() == 0 {
+            println!("");
+            return;
+        }
+
+        let line = line.trim_end_matches(|c| c == '\n' || c == '\r');
+        let (command, arguments) = match line.trim_start().find(' ') {
+            Some(space) => (&line.trim_start()[..space], &line.trim_start()[space + 1..]),
+            None => (line.trim(), ""),
+        };
+
+        let result = match command {
+            "" => Ok(()),
+            "drag" => crib_drag(&drag, arguments, top),
+            "lock" => crib_lock(&mut drag, arguments),
+            "unlock" => {
+                let mut numbers = arguments.split_whitespace().map(|n| n.parse::<usize>());
+                match (numbers.next(), numbers.next(), numbers.next()) {
+                    (Some(Ok(offset)), Some(Ok(length)), None) => Ok(drag.unlock(offset, length)),
+                    _ => Err(String::from("usage: unlock OFFSET LENGTH")),
+                }
+            }
+            "show" => {
+                for message in 0..drag.messages() {
+                    let plaintext: Vec<u8> = drag.plaintext(message).iter().map(|p| p.unwrap_or(b'_')).collect();
+                    println!("{:3}  {}", message + 1, literal.encode(&plaintext));
+                }
+                Ok(())
+            }
+            "key" => {
+                let keystream: Vec<String> = drag.keystream().iter()
+                    .map(|k| k.map_or(String::from(".."), |k| format!("{:02x}", k)))
+                    .collect();
+                println!("{}", keystream.concat());
+                Ok(())
+            }
+            "help" => Ok(println!("{}", CRIB_HELP)),
+            "quit" | "exit" => return,
+            _ => Err(format!("unknown command '{}', type help for the commands", command)),
+        };
+
+        if let Err(error) = result {
+            println!("Error: {}", error);
+        }
+    }
+}
+
+// A message number from 1, as a message index
+fn crib_message(drag: &xor::CribDrag, number: &str) -> Result<usize, String> {
+    match number.parse::<usize>() {
+        Ok(n) if n >= 1 && n <= drag.messages() => Ok(n - 1),
+        _ => Err(format!("invalid message '{}', messages are 1 to {}", number, drag.messages())),
+    }
+}
+
+fn crib_drag(drag: &xor::CribDrag, arguments: &str, top: usize) -> Result<(), String> {
+    let (message, crib) = if arguments.starts_with("-m ") {
+        let mut parts = arguments[3..].trim_start().splitn(2, ' ');
+        let message = crib_message(drag, parts.next().unwrap())?;
+        (Some(message), parts.next().unwrap_or(""))
+    } else {
+        (None, arguments)
+    };
+
+    let literal = Literal::new();
+    let crib = literal.decode(crib).map_err(|error| error.to_string())?;
+    if crib.is_empty() {
+        return Err(String::from("usage: drag [-m M] TEXT"));
+    }
+
+    for result in drag.drag(&crib, message).into_iter().take(top) {
+        let fragments: Vec<String> = result.fragments.iter().map(|f| literal.encode(f)).collect();
+        println!("{:3}:{:<5}  {:.3}  {}", result.message + 1, result.offset, result.score, fragments.join(" | "));
+    }
+
+    Ok(())
+}
+
+fn crib_lock(drag: &mut xor::CribDrag, arguments: &str) -> Result<(), String> {
+    let mut parts = arguments.splitn(3, ' ');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(message), Some(offset), Some(text)) if !text.is_empty() => {
+            let message = crib_message(drag, message)?;
+            let offset = offset.parse::<usize>().map_err(|_| format!("invalid offset '{}'", offset))?;
+            let plaintext = Literal::new().decode(text).map_err(|error| error.to_string())?;
+
+            drag.lock(message, offset, &plaintext);
+            Ok(())
+        }
+        _ => Err(String::from("usage: lock M OFFSET TEXT")),
+    }
+}
+
+// The non empty lines of a file, or of the input, decoded with the encoding along
+// with their line numbers. Lines that cannot be decoded are reported and left out.
+fn read_encoded_lines(encoding: Option<Encoding>, path: Option<&str>) -> (Vec<usize>, Vec<Vec<u8>>) {
+    let input = match path {
+        Some(path) => fs::read(path).unwrap_or_else(|error| exit_with_error(format!("{}: {}", path, error))),
+        None => {
+            let mut input = Vec::new();
+            io::stdin().read_to_end(&mut input).unwrap();
+            input
+        }
+    };
+
+    let mut line_numbers = Vec::new();
+    let mut decoded = Vec::new();
+    for (index, line) in input.split(|&b| b == b'\n').enumerate() {
+        let line = if line.last() == Some(&b'\r') { &line[..line.len() - 1] } else { line };
+        if line.is_empty() {
+            continue;
+        }
+
+        match encoding.map_or(Ok(line.to_vec()), |encoding| encoding.decode(line)) {
+            Ok(bytes) => {
+                line_numbers.push(index + 1);
+                decoded.push(bytes);
+            }
+            Err(error) => eprintln!("Line {}: {}", index + 1, error),
+        }
+    }
+
+    (line_numbers, decoded)
+}
+
+fn run_attack_xor_known(attack: xor::KnownPlaintextAttack) {
+    let mut input = Vec::new();
+    io::stdin().read_to_end(&mut input).unwrap();
+
+    let result = match attack.result(&input) {
+        Some(result) => result,
+        None => exit_with_error("the known plaintext does not give a key: it is past the end of the input or does not match the period"),
+    };
+
+    let mut stdout = io::stdout();
+    println!("Key: {} ({} bytes)", encoding::hex_encode(&result.key), result.key.len());
+    print!("Decrypted:\n");
+    stdout.write_all(&result.plaintext).unwrap();
+}
+
+fn run_attack_xor_many_time(attack: xor::SingleByteAttack, encoding: Option<Encoding>, path: Option<&str>) {
+    let (line_numbers, ciphertexts) = read_encoded_lines(encoding, path);
+
+    let result = xor::ManyTimePadAttack::new()
+        .with_single_byte_attack(attack)
+        .result(&ciphertexts);
+
+    let literal = Literal::new();
+    println!("Keystream: {}", encoding::hex_encode(&result.keystream));
+    for (line, plaintext) in line_numbers.iter().zip(result.plaintexts.iter()) {
+        println!("Line: {}\tResult: {}", line, literal.encode(plaintext));
+    }
+}
+
+fn run_attack_xor_detect(attack: xor::SingleByteAttack, encoding: Option<Encoding>, top: usize, path: Option<&str>) {
+    let (line_numbers, ciphertexts) = read_encoded_lines(encoding, path);
+
+    let results = attack.detect(&ciphertexts);
+
+    let mut stdout = io::stdout();
+    for (index, result) in results.into_iter().take(top) {
+        print!("Line: {}\tKey: {}\tScore: {}\tMargin: {}\tResult: ", line_numbers[index], result.key, result.score, result.margin);
+        stdout.write_all(&result.plaintext).unwrap();
+        println!("");
+    }
+}
+
+fn run_attack_xor_keysize(criterion: Box<KeysizeCriterion>, min: u32, max: u32, threads: usize) {
     let mut input = Vec::new();
     io::stdin().read_to_end(&mut input).unwrap();
 
@@ -353,35 +1171,229 @@ fn run_attack_xor_keysize(criterion: Box<KeysizeCriterion>, min: u32, max: u32)
         .with_min_length(min)
         .with_max_length(max)
         .with_criterion(criterion)
+        .with_threads(threads)
         .result(&input);
 
-    for (size, score) in results {
-        println!("Size: {}\tScore: {}", size, score);
+    for result in results {
+        println!("Size: {}\tScore: {}", result.keysize, result.score);
     }
 }
 
-fn run_attack_xor_repeated(keysize_criterion: Box<KeysizeCriterion>, min: u32, max: u32, keysize_try: usize, xor_criterion: Box<BytesCriterion>, result_criterion: Box<BytesCriterion>) {
+fn run_attack_xor_repeated(attack: xor::RepeatedAttack, refinement: Option<xor::KeyRefinement>, detailed: bool) {
     let mut input = Vec::new();
     io::stdin().read_to_end(&mut input).unwrap();
 
-    let (key, decrypted) = xor::RepeatedAttack::new()
-        .with_single_byte_attack(xor::SingleByteAttack::new()
-                                 .with_criterion(xor_criterion))
-        .with_keysize_attack(xor::KeysizeAttack::new()
-                             .with_min_length(min)
-                             .with_max_length(max)
-                             .with_criterion(keysize_criterion))
-        .with_result_criterion(result_criterion)
-        .with_keysizes_count(keysize_try)
-        .result(&input);
+    let result = attack.result(&input);
+    let (mut key, mut decrypted) = (result.key.clone(), result.plaintext.clone());
+    let mut changed = None;
+    if let Some(refinement) = refinement {
+        let refined = refinement.refine(&input, &key);
+        decrypted = refined.plaintext;
+        changed = Some((key, refined.positions));
+        key = refined.key;
+    }
 
     let mut stdout = io::stdout();
     print!("Key: ");
     stdout.write(&key).unwrap();
+    if let Some((original, positions)) = changed {
+        let changes: Vec<String> = positions.iter()
+            .map(|&i| format!("{}: {:02x} -> {:02x}", i, original[i], key[i]))
+            .collect();
+        print!("\nRefined: {}", if changes.is_empty() { String::from("no change") } else { changes.join(", ") });
+    }
+    if detailed {
+        let margins: Vec<String> = result.margins.iter().map(|m| format!("{}", m)).collect();
+        print!("\nKeysize: {}\tScore: {}\nMargins: {}", result.keysize, result.score, margins.join(", "));
+        for &(ref key, score) in result.candidates.iter() {
+            print!("\nCandidate: {}\tScore: {}", encoding::hex_encode(key), score);
+        }
+    }
     print!("\nDecrypted:\n");
     stdout.write(&decrypted).unwrap();
 }
 
+// The single byte attack set up by the options of attack xor
+fn single_byte_attack(matches: &ArgMatches, default_criterion: &str) -> xor::SingleByteAttack {
+    xor::SingleByteAttack::new()
+        .with_criterion(criterion_or_exit(matches, "criterion", default_criterion))
+        .with_charset(charset(matches))
+        .with_threads(threads(matches))
+}
+
+fn charset(matches: &ArgMatches) -> Charset {
+    if let Some(bytes) = matches.value_of("key-bytes") {
+        match Literal::new().decode(bytes) {
+            Ok(ref decoded) if decoded.is_empty() => exit_with_error("--key-bytes must not be empty"),
+            Ok(decoded) => Charset::Custom(decoded),
+            Err(error) => exit_with_error(format!("invalid key bytes '{}': {}", bytes, error)),
+        }
+    } else {
+        matches.value_of("charset").and_then(Charset::from_name).unwrap_or(Charset::All)
+    }
+}
+
+// The number of threads given to the attacks, by default one per core
+fn threads(matches: &ArgMatches) -> usize {
+    matches.value_of("threads").map_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()), parse_or_exit::<usize>)
+}
+
+fn criterion_or_exit(matches: &ArgMatches, name: &str, default: &str) -> Box<Criterion> {
+    parse_criterion_or_exit(matches.value_of(name).unwrap_or(default))
+}
+
+fn parse_criterion_or_exit(expression: &str) -> Box<Criterion> {
+    match criteria::parse_criterion(expression) {
+        Ok(criterion) => criterion,
+        Err(error) => exit_with_error(format!("invalid criterion '{}': {}", expression, error)),
+    }
+}
+
+fn run_analyze(window: usize, step: usize, top: usize) {
+    let mut input = Vec::new();
+    io::stdin().read_to_end(&mut input).unwrap();
+
+    let entropy = criteria::entropy(&input);
+    let ic = criteria::index_of_coincidence(&input);
+
+    println!("Length: {} bytes", input.len());
+    if input.is_empty() {
+        return;
+    }
+
+    let printable = criteria::printable_text(&input);
+    let kind = if printable >= 0.95 {
+        "text"
+    } else if entropy >= 7.2 {
+        "encrypted or compressed"
+    } else {
+        "binary"
+    };
+
+    println!("Entropy: {:.3} bits per byte", entropy);
+    println!("Index of coincidence: {:.4} ({:.2} times random)", ic, ic * 256.0);
+    println!("Printable: {:.1}%", printable * 100.0);
+    println!("Looks like: {}", kind);
+
+    let histogram = criteria::histogram(&input);
+    let mut counts: Vec<(usize, usize)> = histogram.iter().cloned().enumerate().filter(|&(_, c)| c > 0).collect();
+    counts.sort_by(|&(b1, c1), &(b2, c2)| c2.cmp(&c1).then(b1.cmp(&b2)));
+
+    println!("\nHistogram ({} most frequent of {} distinct bytes):", top.min(counts.len()), counts.len());
+    let max = counts[0].1;
+    for &(byte, count) in counts.iter().take(top) {
+        let symbol = if byte >= 0x20 && byte <= 0x7e { format!("'{}'", byte as u8 as char) } else { String::new() };
+        println!("  0x{:02x} {:3}  {:8}  {:5.1}%  {}", byte, symbol, count, count as f32 * 100.0 / input.len() as f32,
+                 "#".repeat((count * 40 + max - 1) / max));
+    }
+
+    println!("\nEntropy profile (windows of {} bytes):", window.min(input.len()));
+    for (offset, value) in criteria::sliding_entropy(&input, window, step) {
+        println!("  {:08x}  {:.3}  {}", offset, value, "#".repeat((value * 5.0).round() as usize));
+    }
+}
+
+fn run_evaluate_single_byte(evaluation: &Evaluation, expressions: &[&str]) {
+    let results: Vec<Vec<LengthResult>> = expressions.iter().map(|&expression| {
+        let attack = xor::SingleByteAttack::new().with_criterion(parse_criterion_or_exit(expression));
+        evaluation.single_byte(&attack)
+    }).collect();
+
+    println!("Single byte xor:");
+    print_evaluation(expressions, &results);
+}
+
+fn run_evaluate_repeated(evaluation: &Evaluation, expressions: &[&str], max_keysize: usize) {
+    let results: Vec<Vec<LengthResult>> = expressions.iter().map(|&expression| {
+        let attack = xor::RepeatedAttack::new()
+            .with_single_byte_attack(xor::SingleByteAttack::new().with_criterion(parse_criterion_or_exit(expression)))
+            .with_keysize_attack(xor::KeysizeAttack::new().with_max_length(max_keysize as u32))
+            .with_result_criterion(parse_criterion_or_exit(expression));
+        evaluation.repeated(&attack)
+    }).collect();
+
+    println!("Repeated xor, keys of 2 to {} bytes:", max_keysize);
+    print_evaluation(expressions, &results);
+}
+
+// One row per length with the success rate of each criterion
+fn print_evaluation(expressions: &[&str], results: &[Vec<LengthResult>]) {
+    let widths: Vec<usize> = expressions.iter().map(|e| e.len().max(6)).collect();
+
+    print!("{:>8}  {:>6}", "length", "trials");
+    for (expression, &width) in expressions.iter().zip(widths.iter()) {
+        print!("  {:>width$}", expression, width = width);
+    }
+    println!("");
+
+    for (row, first) in results[0].iter().enumerate() {
+        print!("{:>8}  {:>6}", first.length, first.trials);
+        for (result, &width) in results.iter().zip(widths.iter()) {
+            print!("  {:>width$.1}%", result[row].success_rate() * 100.0, width = width - 1);
+        }
+        println!("");
+    }
+}
+
+fn run_list_criteria() {
+    let width = criteria::CRITERIA.iter().map(|&(syntax, _)| syntax.len()).max().unwrap_or(0);
+    for &(syntax, description) in criteria::CRITERIA.iter() {
+        println!("{:width$}  {}", syntax, description, width = width);
+    }
+}
+
+// The encoding of the lines given by the encoding argument, None for raw lines
+fn line_encoding(matches: &ArgMatches) -> Option<Encoding> {
+    match matches.value_of("encoding") {
+        Some("raw") => None,
+        Some(name) => match Encoding::from_name(name) {
+            Some(encoding) => Some(encoding),
+            None => exit_with_error(format!("unknown encoding '{}'", name)),
+        },
+        None => Some(Encoding::Hex),
+    }
+}
+
+fn keysize_criterion(name: Option<&str>) -> Box<KeysizeCriterion> {
+    match name {
+        Some("kasiski") => Box::new(xor::kasiski_criterion),
+        Some("ic") => Box::new(xor::index_of_coincidence_criterion),
+        Some("autocorrelation") => Box::new(xor::autocorrelation_criterion),
+        _ => Box::new(xor::hamming_distance_criterion),
+    }
+}
+
+fn padding_value(matches: &ArgMatches) -> Option<Padding> {
+    match matches.value_of("padding") {
+        Some("required") => Some(Padding::Required),
+        Some("optional") => Some(Padding::Optional),
+        Some(_) => Some(Padding::Omitted),
+        None => None,
+    }
+}
+
+fn literal_engine(matches: &ArgMatches) -> Literal {
+    match matches.value_of("style") {
+        Some("c") => Literal::c(),
+        Some("rust") => Literal::rust(),
+        Some("python") => Literal::python(),
+        _ => Literal::new(),
+    }
+}
+
+fn parse_or_exit<T: FromStr>(value: &str) -> T
+    where T::Err: Display {
+    match value.parse::<T>() {
+        Ok(v) => v,
+        Err(error) => exit_with_error(format!("invalid value '{}': {}", value, error)),
+    }
+}
+
+fn exit_with_error<E: Display>(error: E) -> ! {
+    eprintln!("Error: {}", error);
+    process::exit(1);
+}
+
 fn run_interpreter() {
     println!("Interpreter");
 }
@@ -0,0 +1,224 @@
+use super::Criterion;
+
+// Names of compound criteria are parenthesized when used as operands
+fn operand(criterion: &Criterion) -> String {
+    let name = criterion.name();
+    if name.contains(' ') {
+        format!("({})", name)
+    } else {
+        name
+    }
+}
+
+pub struct Constant(pub f32);
+
+impl Criterion for Constant {
+    fn name(&self) -> String {
+        format!("{}", self.0)
+    }
+
+    fn description(&self) -> String {
+        format!("always {}", self.0)
+    }
+
+    fn score(&self, _: &[u8]) -> f32 {
+        self.0
+    }
+}
+
+pub struct WeightedSum {
+    terms: Vec<(f32, Box<Criterion>)>,
+}
+
+impl WeightedSum {
+    pub fn new() -> WeightedSum {
+        WeightedSum { terms: Vec::new() }
+    }
+
+    pub fn with_term(mut self, weight: f32, criterion: Box<Criterion>) -> WeightedSum {
+        self.terms.push((weight, criterion));
+        self
+    }
+}
+
+impl Criterion for WeightedSum {
+    fn name(&self) -> String {
+        let terms: Vec<String> = self.terms.iter()
+            .map(|&(weight, ref criterion)| format!("{}*{}", weight, operand(criterion.as_ref())))
+            .collect();
+
+        terms.join(" + ")
+    }
+
+    fn description(&self) -> String {
+        format!("weighted sum of {} criteria", self.terms.len())
+    }
+
+    fn score(&self, bytes: &[u8]) -> f32 {
+        self.terms.iter().map(|&(weight, ref criterion)| weight * criterion.score(bytes)).sum()
+    }
+}
+
+pub struct Product {
+    factors: Vec<Box<Criterion>>,
+}
+
+impl Product {
+    pub fn new() -> Product {
+        Product { factors: Vec::new() }
+    }
+
+    pub fn with_factor(mut self, criterion: Box<Criterion>) -> Product {
+        self.factors.push(criterion);
+        self
+    }
+}
+
+impl Criterion for Product {
+    fn name(&self) -> String {
+        let factors: Vec<String> = self.factors.iter().map(|c| operand(c.as_ref())).collect();
+        factors.join("*")
+    }
+
+    fn description(&self) -> String {
+        format!("product of {} criteria", self.factors.len())
+    }
+
+    fn score(&self, bytes: &[u8]) -> f32 {
+        self.factors.iter().map(|c| c.score(bytes)).product()
+    }
+}
+
+pub struct Min {
+    criteria: Vec<Box<Criterion>>,
+}
+
+impl Min {
+    pub fn new() -> Min {
+        Min { criteria: Vec::new() }
+    }
+
+    pub fn with_criterion(mut self, criterion: Box<Criterion>) -> Min {
+        self.criteria.push(criterion);
+        self
+    }
+}
+
+impl Criterion for Min {
+    fn name(&self) -> String {
+        let criteria: Vec<String> = self.criteria.iter().map(|c| c.name()).collect();
+        format!("min({})", criteria.join(", "))
+    }
+
+    fn description(&self) -> String {
+        format!("lowest score of {} criteria", self.criteria.len())
+    }
+
+    fn score(&self, bytes: &[u8]) -> f32 {
+        self.criteria.iter().map(|c| c.score(bytes)).fold(None, |min: Option<f32>, s| {
+            Some(min.map_or(s, |m| m.min(s)))
+        }).unwrap_or(0.0)
+    }
+}
+
+// Scores below the threshold become 0, so a weak match cannot win on other terms
+pub struct Threshold {
+    criterion: Box<Criterion>,
+    threshold: f32,
+}
+
+impl Threshold {
+    pub fn new(criterion: Box<Criterion>, threshold: f32) -> Threshold {
+        Threshold { criterion, threshold }
+    }
+}
+
+impl Criterion for Threshold {
+    fn name(&self) -> String {
+        format!("threshold({}, {})", self.criterion.name(), self.threshold)
+    }
+
+    fn description(&self) -> String {
+        format!("{} when at least {}, 0 otherwise", self.criterion.name(), self.threshold)
+    }
+
+    fn score(&self, bytes: &[u8]) -> f32 {
+        let score = self.criterion.score(bytes);
+        if score >= self.threshold {
+            score
+        } else {
+            0.0
+        }
+    }
+}
+
+pub struct Negate {
+    criterion: Box<Criterion>,
+}
+
+impl Negate {
+    pub fn new(criterion: Box<Criterion>) -> Negate {
+        Negate { criterion }
+    }
+}
+
+impl Criterion for Negate {
+    fn name(&self) -> String {
+        format!("-{}", operand(self.criterion.as_ref()))
+    }
+
+    fn description(&self) -> String {
+        format!("the opposite of {}", self.criterion.name())
+    }
+
+    fn score(&self, bytes: &[u8]) -> f32 {
+        -self.criterion.score(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use criteria::{Criterion, make_common_byte};
+    use super::*;
+
+    #[test]
+    fn weighted_sum() {
+        let criterion = WeightedSum::new()
+            .with_term(0.5, make_common_byte(b'a'))
+            .with_term(2.0, Box::new(Constant(1.0)));
+
+        assert_eq!(criterion.score(b"aabb"), 2.25);
+        assert_eq!(criterion.name(), "0.5*byte(97) + 2*1");
+    }
+
+    #[test]
+    fn product_and_min() {
+        let product = Product::new()
+            .with_factor(make_common_byte(b'a'))
+            .with_factor(make_common_byte(b'b'));
+        let min = Min::new()
+            .with_criterion(make_common_byte(b'a'))
+            .with_criterion(make_common_byte(b'b'));
+
+        assert_eq!(product.score(b"aaab"), 0.1875);
+        assert_eq!(min.score(b"aaab"), 0.25);
+        assert_eq!(Min::new().score(b"aaab"), 0.0);
+        assert_eq!(min.name(), "min(byte(97), byte(98))");
+    }
+
+    #[test]
+    fn threshold_and_negate() {
+        let threshold = Threshold::new(make_common_byte(b'a'), 0.5);
+        let negate = Negate::new(Box::new(Product::new().with_factor(Box::new(Constant(2.0)))));
+
+        assert_eq!(threshold.score(b"aab"), 2.0 / 3.0);
+        assert_eq!(threshold.score(b"abb"), 0.0);
+        assert_eq!(negate.score(b""), -2.0);
+
+        let sum = WeightedSum::new()
+            .with_term(1.0, Box::new(Constant(1.0)))
+            .with_term(0.5, Box::new(Constant(2.0)));
+
+        assert_eq!(Negate::new(Box::new(sum)).name(), "-(1*1 + 0.5*2)");
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use error::Result;
+
+// The most common english words, and some that often turn up in challenges
+const BUILTIN_WORDS: &'static str = "\
+a about after again all also always an and another any are around as at away back be because been
+before being best between both but by call came can come could day did different do does down each
+end even every few find first for found from get give go good great had has have he her here him his
+home house how i if in into is it its just know large last left life like line little live long look
+made make man many may me men might more most much must my name never new next no not now number of
+off old on once one only or other our out over own part people place point put read right said same
+saw say see she should show side small so some something sound still such take tell than that the
+their them then there these they thing think this those thought three through time to together too
+two under up us use very want was water way we well went were what when where which while who why
+will with word work world would write year you young your
+able above across add against air almost along already although am among answer ask asked became
+become began begin behind believe below better big black body book boy bring brought car carry case
+change children city close country course cut dark dead door during early earth eat else enough eye
+face fact family far father feel felt fire five follow food four free friend full gave girl given
+going gone got government group half hand hard head hear heard heart help high hold hope hour human
+idea important keep kind king knew land late later lay lead least leave less let letter light lost
+love making matter mean mind money morning mother move mr mrs near need night nothing often open
+order others paper past perhaps person plan play power present problem question quite rather real
+reason room run saying school sea second seem seemed seen sent set several short since sir six soon
+start state stood story strong sure system table taken talk ten though today told took town tried
+true turn until upon voice walk wanted war watch white whole without woman women words worked
+attack cipher code crypto dawn decrypt encrypt flag hello hidden key message password plain
+plaintext secret text welcome";
+
+// Words are separated by whitespace, in wordlists as in scored texts, so that words
+// such as "hunter2" or "don't" can match. They are compared case-insensitively.
+fn is_separator(b: u8) -> bool {
+    b.is_ascii_whitespace()
+}
+
+fn is_letter(b: u8) -> bool {
+    b.is_ascii_alphabetic()
+}
+
+// Words shorter than this are only counted when they are not surrounded by letters,
+// as short words would otherwise match almost any run of letters
+const MIN_EMBEDDED_LENGTH: usize = 3;
+
+// A list of known words, used to score the proportion of a text that they make up
+pub struct Dictionary {
+    words: HashSet<Vec<u8>>,
+    longest: usize,
+}
+
+impl Dictionary {
+    pub fn new() -> Dictionary {
+        Dictionary { words: HashSet::new(), longest: 0 }
+    }
+
+    // A small list of common english words
+    pub fn builtin() -> Dictionary {
+        Dictionary::from_bytes(BUILTIN_WORDS.as_bytes())
+    }
+
+    // Words separated by whitespace, such as a wordlist with one word per line
+    pub fn from_bytes(bytes: &[u8]) -> Dictionary {
+        bytes.split(|&b| is_separator(b))
+            .filter(|word| !word.is_empty())
+            .fold(Dictionary::new(), |dictionary, word| dictionary.with_word(word))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Dictionary> {
+        Ok(Dictionary::from_bytes(&fs::read(path)?))
+    }
+
+    pub fn with_word(mut self, word: &[u8]) -> Dictionary {
+        self.longest = self.longest.max(word.len());
+        self.words.insert(word.to_ascii_lowercase());
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn contains(&self, word: &[u8]) -> bool {
+        self.words.contains(&word.to_ascii_lowercase())
+    }
+
+    // Number of bytes of a token that are covered by words, which may follow each
+    // other without spaces as in "thisisatest" or be joined by punctuation
+    fn covered(&self, token: &[u8]) -> usize {
+        let token = token.to_ascii_lowercase();
+        if self.words.contains(&token) {
+            return token.len();
+        }
+
+        let bounded = |start: usize, end: usize| {
+            (start == 0 || !is_letter(token[start - 1])) && (end == token.len() || !is_letter(token[end]))
+        };
+
+        // covered[i] is the most bytes of token[..i] that words can cover
+        let mut covered = vec![0; token.len() + 1];
+        for end in 1..=token.len() {
+            covered[end] = covered[end - 1];
+
+            for start in end.saturating_sub(self.longest)..end {
+                if end - start < MIN_EMBEDDED_LENGTH && !bounded(start, end) {
+                    continue;
+                }
+                if self.words.contains(&token[start..end]) {
+                    covered[end] = covered[end].max(covered[start] + end - start);
+                }
+            }
+        }
+
+        covered[token.len()]
+    }
+
+    // Proportion of the bytes that belong to known words
+    pub fn score(&self, bytes: &[u8]) -> f32 {
+        if bytes.is_empty() {
+            return 0.0;
+        }
+
+        let covered: usize = bytes.split(|&b| is_separator(b))
+            .filter(|token| !token.is_empty())
+            .map(|token| self.covered(token))
+            .sum();
+
+        covered as f32 / bytes.len() as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whole_words() {
+        let dictionary = Dictionary::builtin();
+
+        assert_eq!(dictionary.score(b"attack at dawn"), 12.0 / 14.0);
+        assert_eq!(dictionary.score(b"ATTACK AT DAWN"), 12.0 / 14.0);
+        assert_eq!(dictionary.score(b"qzx vjk"), 0.0);
+        assert_eq!(dictionary.score(b""), 0.0);
+        assert!(dictionary.contains(b"Flag"));
+    }
+
+    #[test]
+    fn words_without_spaces() {
+        let dictionary = Dictionary::builtin();
+
+        assert_eq!(dictionary.score(b"flag{hello_world}"), 14.0 / 17.0);
+        assert_eq!(dictionary.score(b"thisisatest"), 4.0 / 11.0);
+        assert_eq!(dictionary.score(b"secretpassword1"), 14.0 / 15.0);
+        assert_eq!(dictionary.score(b"xaix"), 0.0);
+    }
+
+    #[test]
+    fn wordlist() {
+        let dictionary = Dictionary::from_bytes(b"hunter2\nswordfish\r\n\nHunter\n");
+
+        assert_eq!(dictionary.len(), 3);
+        assert_eq!(dictionary.score(b"swordfish"), 1.0);
+        assert_eq!(dictionary.score(b"the swordfish"), 9.0 / 13.0);
+        assert_eq!(dictionary.score(b"hunterhunter"), 1.0);
+        assert_eq!(dictionary.score(b"hunter2"), 1.0);
+        assert_eq!(dictionary.score(b"HUNTER2 swordfish!"), 16.0 / 18.0);
+
+        let dictionary = Dictionary::from_bytes(b"p@ssw0rd
+don't
+");
+        assert_eq!(dictionary.score(b"p@ssw0rd"), 1.0);
+        assert_eq!(dictionary.score(b"(don't)"), 5.0 / 7.0);
+    }
+}
@@ -0,0 +1,455 @@
+use encoding::Literal;
+use error::{Error, Result};
+use super::{Criterion, Dictionary, Named, NgramModel, english_frequency, entropy, index_of_coincidence, json_validity,
+            known_magic, make_common_byte, make_crib, make_magic, make_regex, printable_bytes, printable_text,
+            text_bytes, utf8_validity};
+use super::combinators::{Constant, WeightedSum, Product, Min, Threshold, Negate};
+
+// Syntax and description of the criteria understood by parse_criterion
+pub const CRITERIA: [(&'static str, &'static str); 19] = [
+    ("printable", "proportion of printable ascii bytes"),
+    ("printable_text", "proportion of printable ascii bytes and whitespace, such as line breaks"),
+    ("text", "proportion of letters, spaces and common punctuation"),
+    ("english", "chi-squared fit of the letter frequencies to english"),
+    ("entropy", "bits per byte, up to 8 for random data (-entropy favours plaintext)"),
+    ("ic", "index of coincidence, the probability that two bytes are equal"),
+    ("words", "proportion of bytes in common english words, even without spaces"),
+    ("words:PATH", "proportion of bytes in words of a wordlist, one word per line"),
+    ("utf8", "proportion of bytes in valid UTF-8 sequences"),
+    ("json", "proportion of the bytes read before they stop being valid JSON"),
+    ("magic", "1 when the bytes start with a known file signature"),
+    ("magic:FORMAT", "1 when the bytes start with the signature of FORMAT (pdf, zip, png...)"),
+    ("regex:PATTERN", "1 when the regular expression matches"),
+    ("crib:[N:]TEXT", "proportion of TEXT, with escapes like \\x04, found at offset N (default 0)"),
+    ("byte(N)", "proportion of bytes equal to N"),
+    ("ngram:PATH", "n-gram model trained with 'cryt ngram train'"),
+    ("min(A, B, ...)", "lowest score of the given criteria"),
+    ("threshold(A, T)", "score of A when at least T, 0 otherwise"),
+    ("0.7*A + 0.3*B", "weighted sum, while A*B multiplies and -A negates"),
+];
+
+fn description(syntax: &str) -> &'static str {
+    CRITERIA.iter().find(|&&(s, _)| s == syntax).map_or("", |&(_, d)| d)
+}
+
+fn syntax_error<T>(offset: usize, message: &str) -> Result<T> {
+    Err(Error::Syntax { offset, message: String::from(message) })
+}
+
+// A number, or a sum of weighted criteria kept apart until the whole sum is parsed
+enum Value {
+    Number(f32),
+    Terms(Vec<(f32, Box<Criterion>)>),
+}
+
+impl Value {
+    fn criterion(criterion: Box<Criterion>) -> Value {
+        Value::Terms(vec![(1.0, criterion)])
+    }
+
+    fn into_terms(self) -> Vec<(f32, Box<Criterion>)> {
+        match self {
+            Value::Number(n) => vec![(1.0, Box::new(Constant(n)) as Box<Criterion>)],
+            Value::Terms(terms) => terms,
+        }
+    }
+
+    fn into_criterion(self) -> Box<Criterion> {
+        let mut terms = self.into_terms();
+        if terms.len() == 1 && terms[0].0 == 1.0 {
+            return terms.pop().unwrap().1;
+        }
+
+        if terms.len() == 1 && terms[0].0 == -1.0 {
+            return Box::new(Negate::new(terms.pop().unwrap().1));
+        }
+
+        Box::new(terms.into_iter().fold(WeightedSum::new(), |sum, (weight, c)| sum.with_term(weight, c)))
+    }
+
+    fn scaled(self, factor: f32) -> Value {
+        match self {
+            Value::Number(n) => Value::Number(n * factor),
+            Value::Terms(terms) => Value::Terms(terms.into_iter().map(|(w, c)| (w * factor, c)).collect()),
+        }
+    }
+
+    fn add(self, other: Value) -> Value {
+        match (self, other) {
+            (Value::Number(a), Value::Number(b)) => Value::Number(a + b),
+            (a, b) => {
+                let mut terms = a.into_terms();
+                terms.extend(b.into_terms());
+                Value::Terms(terms)
+            }
+        }
+    }
+
+    fn multiply(self, other: Value) -> Value {
+        match (self, other) {
+            (Value::Number(a), b) => b.scaled(a),
+            (a, Value::Number(b)) => a.scaled(b),
+            (a, b) => {
+                let product = Product::new()
+                    .with_factor(a.into_criterion())
+                    .with_factor(b.into_criterion());
+                Value::criterion(Box::new(product))
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn next_char(&self) -> Option<char> {
+        self.input[self.position..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.next_char() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.position += c.len_utf8();
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.next_char()
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.position += expected.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn take_while<F: Fn(char) -> bool>(&mut self, predicate: F) -> &'a str {
+        let start = self.position;
+        while let Some(c) = self.next_char() {
+            if !predicate(c) {
+                break;
+            }
+            self.position += c.len_utf8();
+        }
+
+        &self.input[start..self.position]
+    }
+
+    fn sum(&mut self) -> Result<Value> {
+        let mut value = self.product()?;
+        loop {
+            if self.eat('+') {
+                value = value.add(self.product()?);
+            } else if self.eat('-') {
+                value = value.add(self.product()?.scaled(-1.0));
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn product(&mut self) -> Result<Value> {
+        let mut value = self.unary()?;
+        while self.eat('*') {
+            value = value.multiply(self.unary()?);
+        }
+
+        Ok(value)
+    }
+
+    fn unary(&mut self) -> Result<Value> {
+        if self.eat('-') {
+            return Ok(self.unary()?.scaled(-1.0));
+        }
+
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Value> {
+        match self.peek() {
+            Some('(') => {
+                self.position += 1;
+                let value = self.sum()?;
+                if !self.eat(')') {
+                    return syntax_error(self.position, "missing ')'");
+                }
+                Ok(value)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let offset = self.position;
+                let number = self.take_while(|c| c.is_ascii_digit() || c == '.');
+                number.parse::<f32>()
+                    .map(Value::Number)
+                    .or_else(|_| syntax_error(offset, "invalid number"))
+            }
+            Some(c) if c.is_ascii_alphabetic() => {
+                let offset = self.position;
+                let name = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_');
+
+                if self.next_char() == Some(':') {
+                    self.position += 1;
+                    let argument = self.argument()?;
+                    return atom(name, Some(&argument), offset).map(Value::criterion);
+                }
+
+                if self.eat('(') {
+                    let arguments = self.arguments()?;
+                    return function(name, arguments, offset);
+                }
+
+                atom(name, None, offset).map(Value::criterion)
+            }
+            Some(_) => syntax_error(self.position, "unexpected character"),
+            None => syntax_error(self.position, "missing criterion"),
+        }
+    }
+
+    // Comma separated expressions up to the closing parenthesis
+    fn arguments(&mut self) -> Result<Vec<(usize, Value)>> {
+        let mut arguments = Vec::new();
+        loop {
+            self.skip_whitespace();
+            arguments.push((self.position, self.sum()?));
+
+            if self.eat(')') {
+                return Ok(arguments);
+            }
+
+            if !self.eat(',') {
+                return syntax_error(self.position, "expected ',' or ')'");
+            }
+        }
+    }
+
+    // The argument of name:argument, either quoted or running up to whitespace, a
+    // comma or a closing parenthesis that are not within parentheses of its own
+    fn argument(&mut self) -> Result<String> {
+        let offset = self.position;
+        if let Some(quote) = self.next_char().filter(|&c| c == '"' || c == '\'') {
+            self.position += 1;
+            let argument = self.take_while(|c| c != quote);
+            if self.next_char() != Some(quote) {
+                return syntax_error(offset, "unterminated quote");
+            }
+
+            self.position += 1;
+            return Ok(String::from(argument));
+        }
+
+        let mut depth = 0;
+        while let Some(c) = self.next_char() {
+            match c {
+                '(' => depth += 1,
+                ')' if depth > 0 => depth -= 1,
+                ')' | ',' => break,
+                c if c.is_whitespace() && depth == 0 => break,
+                _ => {}
+            }
+            self.position += c.len_utf8();
+        }
+
+        if self.position == offset {
+            return syntax_error(offset, "missing argument");
+        }
+
+        Ok(String::from(&self.input[offset..self.position]))
+    }
+}
+
+// I/O errors name the file they come from
+fn with_path(path: &str, error: Error) -> Error {
+    match error {
+        Error::Io(message) => Error::Io(format!("{}: {}", path, message)),
+        error => error,
+    }
+}
+
+fn atom(name: &str, argument: Option<&str>, offset: usize) -> Result<Box<Criterion>> {
+    let criterion: Box<Criterion> = match (name, argument) {
+        ("printable", None) => Box::new(Named::new(name, description(name), printable_bytes)),
+        ("printable_text", None) => Box::new(Named::new(name, description(name), printable_text)),
+        ("text", None) => Box::new(Named::new(name, description(name), text_bytes)),
+        ("english", None) => Box::new(Named::new(name, description(name), english_frequency)),
+        ("entropy", None) => Box::new(Named::new(name, description(name), entropy)),
+        ("ic", None) => Box::new(Named::new(name, description(name), index_of_coincidence)),
+        ("words", None) => {
+            let dictionary = Dictionary::builtin();
+            Box::new(Named::new(name, description(name), move |bytes: &[u8]| dictionary.score(bytes)))
+        }
+        ("words", Some(path)) => {
+            let dictionary = Dictionary::load(path).map_err(|error| with_path(path, error))?;
+
+            let name = format!("words:{}", path);
+            Box::new(Named::new(&name, description("words:PATH"), move |bytes: &[u8]| dictionary.score(bytes)))
+        }
+        ("ngram", Some(path)) => {
+            let model = NgramModel::load(path).map_err(|error| with_path(path, error))?;
+
+            let name = format!("ngram:{}", path);
+            Box::new(Named::new(&name, description("ngram:PATH"), move |bytes: &[u8]| model.score(bytes)))
+        }
+        ("utf8", None) => Box::new(Named::new(name, description(name), utf8_validity)),
+        ("json", None) => Box::new(Named::new(name, description(name), json_validity)),
+        ("magic", None) => Box::new(Named::new(name, description(name), known_magic)),
+        ("magic", Some(format)) => match make_magic(format) {
+            Some(criterion) => criterion,
+            None => return syntax_error(offset + name.len() + 1, "unknown file format"),
+        },
+        ("regex", Some(pattern)) => make_regex(pattern).map_err(|error| match error {
+            Error::Syntax { message, .. } => Error::Syntax { offset: offset + name.len() + 1, message },
+            error => error,
+        })?,
+        ("crib", Some(argument)) => crib(argument, offset + name.len() + 1)?,
+        ("ngram", None) | ("regex", None) | ("crib", None) => return syntax_error(offset + name.len(), "missing argument"),
+        (_, Some(_)) if CRITERIA.iter().any(|&(syntax, _)| syntax == name) => {
+            return syntax_error(offset + name.len(), "unexpected argument")
+        }
+        _ => return syntax_error(offset, "unknown criterion"),
+    };
+
+    Ok(criterion)
+}
+
+// TEXT or N:TEXT, where the text may contain escapes for arbitrary bytes
+fn crib(argument: &str, offset: usize) -> Result<Box<Criterion>> {
+    let (position, text) = match argument.find(':') {
+        Some(colon) if colon > 0 && argument[..colon].bytes().all(|b| b.is_ascii_digit()) => {
+            let position = argument[..colon].parse::<usize>().or_else(|_| syntax_error(offset, "invalid number"))?;
+            (position, &argument[colon + 1..])
+        }
+        _ => (0, argument),
+    };
+
+    Ok(make_crib(position, &Literal::new().decode(text)?))
+}
+
+fn function(name: &str, arguments: Vec<(usize, Value)>, offset: usize) -> Result<Value> {
+    let count = arguments.len();
+    let mut arguments = arguments.into_iter();
+
+    let criterion: Box<Criterion> = match (name, count) {
+        ("byte", 1) => match arguments.next().unwrap() {
+            (_, Value::Number(n)) if n >= 0.0 && n <= 255.0 && n.fract() == 0.0 => make_common_byte(n as u8),
+            (offset, _) => return syntax_error(offset, "expected a byte value"),
+        },
+        ("min", _) => Box::new(arguments.fold(Min::new(), |min, (_, value)| min.with_criterion(value.into_criterion()))),
+        ("threshold", 2) => {
+            let criterion = arguments.next().unwrap().1.into_criterion();
+            match arguments.next().unwrap() {
+                (_, Value::Number(threshold)) => Box::new(Threshold::new(criterion, threshold)),
+                (offset, _) => return syntax_error(offset, "expected a number"),
+            }
+        }
+        ("byte", _) | ("threshold", _) => return syntax_error(offset, "wrong number of arguments"),
+        _ => return syntax_error(offset, "unknown function"),
+    };
+
+    Ok(Value::criterion(criterion))
+}
+
+// Builds a criterion from an expression such as "0.7*english + 0.3*byte(32)".
+// Numbers weigh criteria, criteria multiplied together multiply their scores and
+// parentheses group as usual. See CRITERIA for the available criteria.
+pub fn parse_criterion(expression: &str) -> Result<Box<Criterion>> {
+    let mut parser = Parser { input: expression, position: 0 };
+    let value = parser.sum()?;
+
+    if parser.peek().is_some() {
+        return syntax_error(parser.position, "unexpected character");
+    }
+
+    Ok(value.into_criterion())
+}
+
+#[cfg(test)]
+mod tests {
+    use error::Error;
+    use super::*;
+
+    fn error(expression: &str) -> Error {
+        parse_criterion(expression).err().unwrap()
+    }
+
+    #[test]
+    fn builtin_criteria() {
+        let criterion = parse_criterion("printable").unwrap();
+        assert_eq!(criterion.score(b"ab\x00\x01"), 0.5);
+        assert_eq!(criterion.name(), "printable");
+        assert_eq!(criterion.description(), "proportion of printable ascii bytes");
+
+        assert_eq!(parse_criterion(" byte( 32 ) ").unwrap().score(b"a b "), 0.5);
+        assert_eq!(parse_criterion("-entropy").unwrap().score(b"abab"), -1.0);
+        assert_eq!(parse_criterion("ic").unwrap().score(b"aaaa"), 1.0);
+        assert_eq!(parse_criterion("words").unwrap().score(b"hello world"), 10.0 / 11.0);
+    }
+
+    #[test]
+    fn weighted_sum() {
+        let criterion = parse_criterion("0.75*byte(97) + 0.25 * byte(98)").unwrap();
+        assert_eq!(criterion.score(b"aaab"), 0.625);
+        assert_eq!(criterion.name(), "0.75*byte(97) + 0.25*byte(98)");
+    }
+
+    #[test]
+    fn precedence_and_grouping() {
+        assert_eq!(parse_criterion("2*(byte(97) - byte(98))").unwrap().score(b"aaab"), 1.0);
+        assert_eq!(parse_criterion("byte(97)*byte(98) + 1").unwrap().score(b"aaab"), 1.1875);
+        assert_eq!(parse_criterion("-byte(97)").unwrap().score(b"aaab"), -0.75);
+        assert_eq!(parse_criterion("-byte(97)").unwrap().name(), "-byte(97)");
+    }
+
+    #[test]
+    fn functions() {
+        assert_eq!(parse_criterion("min(byte(97), byte(98), 0.5)").unwrap().score(b"aaab"), 0.25);
+        assert_eq!(parse_criterion("threshold(byte(97), 0.8)").unwrap().score(b"aaab"), 0.0);
+        assert_eq!(parse_criterion("threshold(byte(97), 0.5)").unwrap().score(b"aaab"), 0.75);
+    }
+
+    #[test]
+    fn atom_arguments() {
+        assert_eq!(error("ngram:'/no such/model'"), Error::Io(String::from("/no such/model: No such file or directory (os error 2)")));
+        assert_eq!(error("min(ngram:/no/model(1), text)"), Error::Io(String::from("/no/model(1): No such file or directory (os error 2)")));
+        assert_eq!(error("words:/no/words"), Error::Io(String::from("/no/words: No such file or directory (os error 2)")));
+        assert_eq!(error("regex:'a[b'"), Error::Syntax { offset: 6, message: String::from("invalid regex: unclosed character class") });
+        assert_eq!(error("magic:docx"), Error::Syntax { offset: 6, message: String::from("unknown file format") });
+        assert_eq!(error("crib"), Error::Syntax { offset: 4, message: String::from("missing argument") });
+        assert_eq!(error("ngram:'unterminated"), Error::Syntax { offset: 6, message: String::from("unterminated quote") });
+        assert_eq!(error("ngram: text"), Error::Syntax { offset: 6, message: String::from("missing argument") });
+    }
+
+    #[test]
+    fn format_criteria() {
+        assert_eq!(parse_criterion(r"regex:flag\{[^}]+\}").unwrap().score(b"flag{x}"), 1.0);
+        assert_eq!(parse_criterion("regex:'^(a|b), c$'").unwrap().score(b"b, c"), 1.0);
+        assert_eq!(parse_criterion(r"crib:PK\x03\x04").unwrap().score(b"PK\x03\x05"), 0.75);
+        assert_eq!(parse_criterion("crib:3:{").unwrap().score(b"abc{"), 1.0);
+        assert_eq!(parse_criterion("crib:3:{").unwrap().name(), "crib:3:{");
+        assert_eq!(parse_criterion("magic:pdf").unwrap().score(b"%PDF-1.4"), 1.0);
+        assert_eq!(parse_criterion("0.5*json + 0.5*utf8").unwrap().score(b"{}"), 1.0);
+        assert_eq!(parse_criterion("magic").unwrap().score(b"\x7fELF\x02"), 1.0);
+    }
+
+    #[test]
+    fn syntax_errors() {
+        assert_eq!(error("englsh"), Error::Syntax { offset: 0, message: String::from("unknown criterion") });
+        assert_eq!(error("0.5*foo(1)"), Error::Syntax { offset: 4, message: String::from("unknown function") });
+        assert_eq!(error("english +"), Error::Syntax { offset: 9, message: String::from("missing criterion") });
+        assert_eq!(error("(english"), Error::Syntax { offset: 8, message: String::from("missing ')'") });
+        assert_eq!(error("english text"), Error::Syntax { offset: 8, message: String::from("unexpected character") });
+        assert_eq!(error("byte(300)"), Error::Syntax { offset: 5, message: String::from("expected a byte value") });
+        assert_eq!(error("byte(1, 2)"), Error::Syntax { offset: 0, message: String::from("wrong number of arguments") });
+        assert_eq!(error("min(text; english)"), Error::Syntax { offset: 8, message: String::from("expected ',' or ')'") });
+        assert_eq!(error("1..2*text"), Error::Syntax { offset: 0, message: String::from("invalid number") });
+        assert_eq!(error("english:uk"), Error::Syntax { offset: 7, message: String::from("unexpected argument") });
+        assert_eq!(error("text $"), Error::Syntax { offset: 5, message: String::from("unexpected character") });
+    }
+}
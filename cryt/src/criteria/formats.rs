@@ -0,0 +1,283 @@
+use std::str;
+
+// Signatures at the start of common file formats
+pub const MAGICS: [(&'static str, &'static [u8]); 12] = [
+    ("pdf", b"%PDF-"),
+    ("zip", b"PK\x03\x04"),
+    ("png", b"\x89PNG\r\n\x1a\n"),
+    ("jpeg", b"\xff\xd8\xff"),
+    ("gif", b"GIF8"),
+    ("gzip", b"\x1f\x8b"),
+    ("bzip2", b"BZh"),
+    ("7z", b"7z\xbc\xaf\x27\x1c"),
+    ("elf", b"\x7fELF"),
+    ("pe", b"MZ"),
+    ("class", b"\xca\xfe\xba\xbe"),
+    ("sqlite", b"SQLite format 3\x00"),
+];
+
+pub fn magic(name: &str) -> Option<&'static [u8]> {
+    MAGICS.iter().find(|&&(n, _)| n == name).map(|&(_, magic)| magic)
+}
+
+// 1 when the bytes start with the signature of one of the known formats, 0 otherwise
+pub fn known_magic(bytes: &[u8]) -> f32 {
+    if MAGICS.iter().any(|&(_, magic)| bytes.starts_with(magic)) {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+// Proportion of the bytes that are part of valid UTF-8 sequences
+pub fn utf8_validity(bytes: &[u8]) -> f32 {
+    if bytes.is_empty() {
+        return 0.0;
+    }
+
+    let mut valid = 0;
+    let mut rest = bytes;
+    while let Err(error) = str::from_utf8(rest) {
+        valid += error.valid_up_to();
+        let invalid = error.error_len().unwrap_or(rest.len() - error.valid_up_to());
+        rest = &rest[error.valid_up_to() + invalid..];
+    }
+    valid += rest.len();
+
+    valid as f32 / bytes.len() as f32
+}
+
+// Checks that the input is a single JSON value, surrounded by optional whitespace
+struct JsonValidator<'a> {
+    input: &'a [u8],
+    position: usize,
+}
+
+// Nested values beyond this depth are taken as invalid rather than overflowing the stack
+const MAX_JSON_DEPTH: usize = 128;
+
+impl<'a> JsonValidator<'a> {
+    fn next(&self) -> Option<u8> {
+        self.input.get(self.position).cloned()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') = self.next() {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: &[u8]) -> Option<()> {
+        if self.input[self.position..].starts_with(expected) {
+            self.position += expected.len();
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    fn digits(&mut self) -> usize {
+        let start = self.position;
+        while let Some(b'0'..=b'9') = self.next() {
+            self.position += 1;
+        }
+
+        self.position - start
+    }
+
+    fn value(&mut self, depth: usize) -> Option<()> {
+        if depth > MAX_JSON_DEPTH {
+            return None;
+        }
+
+        self.skip_whitespace();
+        match self.next()? {
+            b'{' => self.object(depth),
+            b'[' => self.array(depth),
+            b'"' => self.string(),
+            b'-' | b'0'..=b'9' => self.number(),
+            b't' => self.expect(b"true"),
+            b'f' => self.expect(b"false"),
+            b'n' => self.expect(b"null"),
+            _ => None,
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Option<()> {
+        self.position += 1;
+        self.skip_whitespace();
+        if self.next() == Some(b'}') {
+            self.position += 1;
+            return Some(());
+        }
+
+        loop {
+            self.skip_whitespace();
+            if self.next() != Some(b'"') {
+                return None;
+            }
+            self.string()?;
+            self.skip_whitespace();
+            self.expect(b":")?;
+            self.value(depth + 1)?;
+            self.skip_whitespace();
+
+            match self.next()? {
+                b',' => self.position += 1,
+                b'}' => {
+                    self.position += 1;
+                    return Some(());
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Option<()> {
+        self.position += 1;
+        self.skip_whitespace();
+        if self.next() == Some(b']') {
+            self.position += 1;
+            return Some(());
+        }
+
+        loop {
+            self.value(depth + 1)?;
+            self.skip_whitespace();
+
+            match self.next()? {
+                b',' => self.position += 1,
+                b']' => {
+                    self.position += 1;
+                    return Some(());
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    fn string(&mut self) -> Option<()> {
+        self.position += 1;
+        let start = self.position;
+        loop {
+            match self.next()? {
+                b'"' => break,
+                b'\\' => {
+                    self.position += 1;
+                    match self.next()? {
+                        b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't' => self.position += 1,
+                        b'u' => {
+                            self.position += 1;
+                            for _ in 0..4 {
+                                if !self.next()?.is_ascii_hexdigit() {
+                                    return None;
+                                }
+                                self.position += 1;
+                            }
+                        }
+                        _ => return None,
+                    }
+                }
+                0x00..=0x1f => return None,
+                _ => self.position += 1,
+            }
+        }
+
+        str::from_utf8(&self.input[start..self.position]).ok()?;
+        self.position += 1;
+        Some(())
+    }
+
+    fn number(&mut self) -> Option<()> {
+        if self.next() == Some(b'-') {
+            self.position += 1;
+        }
+
+        match self.next()? {
+            b'0' => self.position += 1,
+            b'1'..=b'9' => {
+                self.digits();
+            }
+            _ => return None,
+        }
+
+        if self.next() == Some(b'.') {
+            self.position += 1;
+            if self.digits() == 0 {
+                return None;
+            }
+        }
+
+        if let Some(b'e') | Some(b'E') = self.next() {
+            self.position += 1;
+            if let Some(b'+') | Some(b'-') = self.next() {
+                self.position += 1;
+            }
+            if self.digits() == 0 {
+                return None;
+            }
+        }
+
+        Some(())
+    }
+}
+
+// Proportion of the bytes read before the input stops being valid JSON, so that
+// 1 means a complete JSON document and a truncated one still scores high
+pub fn json_validity(bytes: &[u8]) -> f32 {
+    if bytes.is_empty() {
+        return 0.0;
+    }
+
+    let mut validator = JsonValidator { input: bytes, position: 0 };
+    let valid = validator.value(0).map(|_| {
+        validator.skip_whitespace();
+        validator.position == bytes.len()
+    });
+
+    if valid == Some(true) {
+        1.0
+    } else {
+        // Input that ends before the document does stops short of 1
+        validator.position.min(bytes.len() - 1) as f32 / bytes.len() as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn magics() {
+        assert_eq!(known_magic(b"%PDF-1.7\n"), 1.0);
+        assert_eq!(known_magic(b"PK\x03\x04\x14\x00"), 1.0);
+        assert_eq!(known_magic(b"PK\x03"), 0.0);
+        assert_eq!(magic("png"), Some(&b"\x89PNG\r\n\x1a\n"[..]));
+        assert_eq!(magic("docx"), None);
+    }
+
+    #[test]
+    fn utf8() {
+        assert_eq!(utf8_validity("caf\u{e9}".as_bytes()), 1.0);
+        assert_eq!(utf8_validity(b"ab\xff\xfe"), 0.5);
+        assert_eq!(utf8_validity(b"ab\xc3"), 2.0 / 3.0);
+        assert_eq!(utf8_validity(b""), 0.0);
+    }
+
+    #[test]
+    fn valid_json() {
+        assert_eq!(json_validity(b"{\"a\": [1, -2.5e3, true, null], \"b\": {\"c\": \"\\u00e9\\n\"}}"), 1.0);
+        assert_eq!(json_validity(b" [] "), 1.0);
+        assert_eq!(json_validity(b"0"), 1.0);
+    }
+
+    #[test]
+    fn invalid_json() {
+        assert_eq!(json_validity(b"{\"flag\": tru}"), 9.0 / 13.0);
+        assert_eq!(json_validity(b"{\"a\": 1"), 6.0 / 7.0);
+        assert_eq!(json_validity(b"[1] 2"), 0.8);
+        assert_eq!(json_validity(b"[01]"), 0.5);
+        assert_eq!(json_validity(b"\x89PNG"), 0.0);
+        assert_eq!(json_validity(&[b'['; 1000]), 129.0 / 1000.0);
+    }
+}
@@ -0,0 +1,267 @@
+use std::collections::HashSet;
+use std::iter::FromIterator;
+
+use regex::bytes::Regex;
+
+use error::{Error, Result};
+
+mod combinators;
+mod dictionary;
+mod expression;
+mod formats;
+mod ngram;
+mod statistics;
+
+pub use self::combinators::{Constant, WeightedSum, Product, Min, Threshold, Negate};
+pub use self::dictionary::Dictionary;
+pub use self::expression::{parse_criterion, CRITERIA};
+pub use self::formats::{MAGICS, magic, known_magic, utf8_validity, json_validity};
+pub use self::ngram::NgramModel;
+pub use self::statistics::{histogram, entropy, sliding_entropy, index_of_coincidence};
+
+// Scores how likely some bytes are to be the expected plaintext, higher is better.
+// Criteria are shared by the threads of the attacks, hence Sync.
+pub trait Criterion: Sync {
+    fn name(&self) -> String;
+    fn description(&self) -> String;
+    fn score(&self, bytes: &[u8]) -> f32;
+}
+
+impl<F: Fn(&[u8]) -> f32 + Sync> Criterion for F {
+    fn name(&self) -> String {
+        String::from("custom")
+    }
+
+    fn description(&self) -> String {
+        String::from("custom scoring function")
+    }
+
+    fn score(&self, bytes: &[u8]) -> f32 {
+        self(bytes)
+    }
+}
+
+// A scoring function with a name and a description
+pub struct Named<F: Fn(&[u8]) -> f32> {
+    name: String,
+    description: String,
+    function: F,
+}
+
+impl<F: Fn(&[u8]) -> f32> Named<F> {
+    pub fn new(name: &str, description: &str, function: F) -> Named<F> {
+        Named {
+            name: String::from(name),
+            description: String::from(description),
+            function,
+        }
+    }
+}
+
+impl<F: Fn(&[u8]) -> f32 + Sync> Criterion for Named<F> {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn score(&self, bytes: &[u8]) -> f32 {
+        (self.function)(bytes)
+    }
+}
+
+const TEXT_BYTES: [u8; 59] = ['a' as u8, 'b' as u8, 'c' as u8, 'd' as u8, 'e' as u8, 'f' as u8, 'g' as u8, 'h' as u8, 'i' as u8, 'j' as u8, 'k' as u8, 'l' as u8, 'm' as u8, 'n' as u8, 'o' as u8, 'p' as u8, 'q' as u8, 'r' as u8, 's' as u8, 't' as u8, 'u' as u8, 'v' as u8, 'w' as u8, 'x' as u8, 'y' as u8, 'z' as u8, 'A' as u8, 'B' as u8, 'C' as u8, 'D' as u8, 'E' as u8, 'F' as u8, 'G' as u8, 'H' as u8, 'I' as u8, 'J' as u8, 'K' as u8, 'L' as u8, 'M' as u8, 'N' as u8, 'O' as u8, 'P' as u8, 'Q' as u8, 'R' as u8, 'S' as u8, 'T' as u8, 'U' as u8, 'V' as u8, 'W' as u8, 'X' as u8, 'Y' as u8, 'Z' as u8, ' ' as u8, ',' as u8, '.' as u8, '\'' as u8, '!' as u8, ';' as u8, ':' as u8];
+
+pub fn printable_bytes(bytes: &[u8]) -> f32 {
+    bytes
+        .iter()
+        .filter(|&&b| b >= 0x20 && b <= 0x7e)
+        .collect::<Vec<_>>()
+        .len() as f32 / (bytes.len() as f32)
+}
+
+// Proportion of printable bytes, where whitespace such as line breaks and tabs
+// also counts, so that multi-line text scores as text
+pub fn printable_text(bytes: &[u8]) -> f32 {
+    bytes.iter().filter(|&&b| (b >= 0x20 && b <= 0x7e) || b.is_ascii_whitespace()).count() as f32 / bytes.len() as f32
+}
+
+pub fn text_bytes(bytes: &[u8]) -> f32 {
+    let set: HashSet<u8> = HashSet::from_iter(TEXT_BYTES.iter().cloned());
+    bytes
+        .iter()
+        .filter(|&b| set.contains(b))
+        .collect::<Vec<_>>()
+        .len() as f32 / bytes.len() as f32
+        
+}
+
+pub fn make_common_byte(byte: u8) -> Box<Criterion> {
+    let criterion = move |bytes: &[u8]| {
+        bytes
+            .iter()
+            .filter(|&&b| b == byte)
+            .collect::<Vec<_>>()
+            .len() as f32 / bytes.len() as f32
+    };
+
+    Box::new(Named::new(&format!("byte({})", byte), &format!("proportion of bytes equal to {}", byte), criterion))
+}
+
+// 1 when the regular expression matches anywhere in the bytes, 0 otherwise
+pub fn make_regex(pattern: &str) -> Result<Box<Criterion>> {
+    let regex = Regex::new(pattern).map_err(|error| {
+        // Only keep the last line of the message, below the pattern and its marker
+        let error = error.to_string();
+        let message = error.lines().last().unwrap_or("");
+        Error::Syntax { offset: 0, message: format!("invalid regex: {}", message.trim_start_matches("error: ")) }
+    })?;
+
+    let criterion = move |bytes: &[u8]| if regex.is_match(bytes) { 1.0 } else { 0.0 };
+    Ok(Box::new(Named::new(&format!("regex:{}", pattern), "1 when the regular expression matches", criterion)))
+}
+
+// Proportion of the crib that is found at the given offset, so that a partial
+// match still scores
+pub fn make_crib(offset: usize, crib: &[u8]) -> Box<Criterion> {
+    let expected = crib.to_vec();
+    let criterion = move |bytes: &[u8]| {
+        if expected.is_empty() {
+            return 1.0;
+        }
+
+        let found = bytes.iter().skip(offset).zip(expected.iter()).filter(|&(a, b)| a == b).count();
+        found as f32 / expected.len() as f32
+    };
+
+    let name = format!("crib:{}:{}", offset, String::from_utf8_lossy(crib).escape_default());
+    Box::new(Named::new(&name, &format!("proportion of the crib found at offset {}", offset), criterion))
+}
+
+// 1 when the bytes start with the signature of the given format, see MAGICS
+pub fn make_magic(name: &str) -> Option<Box<Criterion>> {
+    let signature = magic(name)?;
+    let criterion = move |bytes: &[u8]| if bytes.starts_with(signature) { 1.0 } else { 0.0 };
+
+    Some(Box::new(Named::new(&format!("magic:{}", name), &format!("1 when the bytes start with the {} signature", name), criterion)))
+}
+
+// Relative frequencies of 'a' to 'z' and ' ' in english text
+const ENGLISH_LETTER_FREQUENCIES: [f32; 26] = [0.0653, 0.0126, 0.0223, 0.0328, 0.1027, 0.0198, 0.0162, 0.0498, 0.0567, 0.0010, 0.0056, 0.0331, 0.0202, 0.0571, 0.0616, 0.0150, 0.0008, 0.0499, 0.0532, 0.0752, 0.0228, 0.0080, 0.0170, 0.0014, 0.0143, 0.0005];
+const ENGLISH_SPACE_FREQUENCY: f32 = 0.1829;
+
+// Digits, punctuation and line breaks take a share of the letters, control and
+// non ascii bytes are expected so rarely that any of them weighs heavily
+const ENGLISH_LETTERS_SHARE: f32 = 0.93;
+const ENGLISH_OTHER_FREQUENCY: f32 = 0.07;
+const ENGLISH_INVALID_FREQUENCY: f32 = 0.0001;
+
+// Chi-squared distance between the case-folded byte distribution and that of english,
+// mapped to (0, 1] so that, like the other criteria, higher is better
+pub fn english_frequency(bytes: &[u8]) -> f32 {
+    if bytes.is_empty() {
+        return 0.0;
+    }
+
+    let mut letters = [0u32; 26];
+    let mut spaces = 0;
+    let mut others = 0;
+    let mut invalid = 0;
+
+    for &b in bytes {
+        match b {
+            b'a'..=b'z' => letters[(b - b'a') as usize] += 1,
+            b'A'..=b'Z' => letters[(b - b'A') as usize] += 1,
+            b' ' => spaces += 1,
+            b'\n' | b'\r' | b'\t' | 0x21..=0x7e => others += 1,
+            _ => invalid += 1,
+        }
+    }
+
+    let length = bytes.len() as f32;
+    let chi_squared_term = |observed: u32, frequency: f32| {
+        let expected = frequency * length;
+        (observed as f32 - expected).powi(2) / expected
+    };
+
+    let mut chi_squared = chi_squared_term(spaces, ENGLISH_SPACE_FREQUENCY * ENGLISH_LETTERS_SHARE)
+        + chi_squared_term(others, ENGLISH_OTHER_FREQUENCY)
+        + chi_squared_term(invalid, ENGLISH_INVALID_FREQUENCY);
+
+    for (&count, &frequency) in letters.iter().zip(ENGLISH_LETTER_FREQUENCIES.iter()) {
+        chi_squared += chi_squared_term(count, frequency * ENGLISH_LETTERS_SHARE);
+    }
+
+    1.0 / (1.0 + chi_squared / length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_printable_bytes() {
+        let input = vec!['a' as u8, 'b' as u8, 0x01, 0x15, 'c' as u8];
+        assert_eq!(printable_bytes(&input), 3.0/5.0);
+    }
+
+    #[test]
+    fn test_printable_text() {
+        assert_eq!(printable_text(b"two\r\nlines\tand a tab\n"), 1.0);
+        assert_eq!(printable_text(b"ab\x01\x0bc\xff"), 3.0/6.0);
+    }
+
+    #[test]
+    fn test_text_bytes() {
+        let input = "h el.l'o$& bye! bye#@".as_bytes();
+        assert_eq!(text_bytes(&input[..].to_vec()), 17.0/21.0);
+    }
+
+    #[test]
+    fn test_english_frequency() {
+        let english = english_frequency(b"The quick brown fox jumps over the lazy dog, then naps in the sun.");
+        let gibberish = english_frequency(b"zzzz qqqq jjjj xxxx vvvv kkkk zzzz qqqq jjjj xxxx vvvv kkkk zz qq");
+        let control = english_frequency(b"The quick brown fox jumps over the lazy dog\x01\x02\x03\x04\x05");
+
+        assert!(english > gibberish);
+        assert!(english > control);
+        assert!(english > 0.0 && english <= 1.0);
+        assert_eq!(english_frequency(b"ATTACK AT DAWN"), english_frequency(b"attack at dawn"));
+        assert_eq!(english_frequency(b""), 0.0);
+    }
+
+    #[test]
+    fn test_common_byte() {
+        let a_criterion = make_common_byte('a' as u8);
+        let input = "axaxax".as_bytes();
+        assert_eq!(a_criterion.score(&input), 3.0/6.0);
+        assert_eq!(a_criterion.name(), "byte(97)");
+    }
+
+    #[test]
+    fn test_regex() {
+        let criterion = make_regex(r"flag\{[^}]+\}").unwrap();
+        assert_eq!(criterion.score(b"the flag{x0r} is here"), 1.0);
+        assert_eq!(criterion.score(b"the flag{} is here"), 0.0);
+        assert_eq!(criterion.score(b"\xffflag{\x00}"), 1.0);
+
+        let error = make_regex(r"flag\{[x").err().unwrap();
+        assert_eq!(error, Error::Syntax { offset: 0, message: String::from("invalid regex: unclosed character class") });
+    }
+
+    #[test]
+    fn test_crib_and_magic() {
+        let crib = make_crib(2, b"flag{");
+        assert_eq!(crib.score(b"  flag{abc}"), 1.0);
+        assert_eq!(crib.score(b"  flax{abc}"), 0.8);
+        assert_eq!(crib.score(b"  fl"), 0.4);
+        assert_eq!(crib.name(), "crib:2:flag{");
+
+        let zip = make_magic("zip").unwrap();
+        assert_eq!(zip.score(b"PK\x03\x04\x14\x00"), 1.0);
+        assert_eq!(zip.score(b"%PDF-1.4"), 0.0);
+        assert!(make_magic("docx").is_none());
+    }
+}
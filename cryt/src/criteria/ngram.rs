@@ -0,0 +1,323 @@
+use std::fs;
+use std::path::Path;
+
+use error::{Error, Result};
+
+const MAGIC: &'static [u8; 8] = b"CRYTNGRM";
+const VERSION: u8 = 1;
+const MAX_ORDER: usize = 4;
+
+// Letters are folded to lowercase and runs of whitespace to a single space, the
+// remaining printable bytes share a symbol and so do all other bytes
+const SYMBOLS: usize = 29;
+const SPACE: usize = 26;
+const PUNCTUATION: usize = 27;
+const BINARY: usize = 28;
+
+fn symbol(b: u8) -> usize {
+    match b {
+        b'a'..=b'z' => (b - b'a') as usize,
+        b'A'..=b'Z' => (b - b'A') as usize,
+        b' ' | b'\t' | b'\n' | b'\r' => SPACE,
+        0x21..=0x7e => PUNCTUATION,
+        _ => BINARY,
+    }
+}
+
+// Digits and punctuation, and the bytes that are neither text nor whitespace
+const PUNCTUATION_BYTES: f32 = 42.0;
+const BINARY_BYTES: f32 = 158.0;
+
+// A symbol that stands for several bytes shares its probability among them, so
+// that e.g. a run of digits is not as likely as a frequent word
+fn symbol_log_weight(symbol: usize) -> f32 {
+    match symbol {
+        PUNCTUATION => -PUNCTUATION_BYTES.log10(),
+        BINARY => -BINARY_BYTES.log10(),
+        _ => 0.0,
+    }
+}
+
+fn push_symbols(bytes: &[u8], symbols: &mut Vec<usize>) {
+    for &b in bytes {
+        let s = symbol(b);
+        if s != SPACE || symbols.last() != Some(&SPACE) {
+            symbols.push(s);
+        }
+    }
+}
+
+fn index(window: &[usize]) -> usize {
+    window.iter().fold(0, |acc, &s| acc * SYMBOLS + s)
+}
+
+fn window_log_weight(mut index: usize, order: usize) -> f32 {
+    let mut weight = 0.0;
+    for _ in 0..order {
+        weight += symbol_log_weight(index % SYMBOLS);
+        index /= SYMBOLS;
+    }
+
+    weight
+}
+
+fn check_order(order: usize) -> Result<()> {
+    if order < 1 || order > MAX_ORDER {
+        return Err(Error::InvalidModel(format!("order must be between 1 and {}, got {}", MAX_ORDER, order)));
+    }
+
+    Ok(())
+}
+
+fn push_varint(mut value: u64, output: &mut Vec<u8>) {
+    while value >= 0x80 {
+        output.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    output.push(value as u8);
+}
+
+fn read_varint(input: &[u8], position: &mut usize) -> Result<u64> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let b = *input.get(*position).ok_or(Error::InvalidModel(String::from("truncated data")))?;
+        *position += 1;
+        value |= ((b & 0x7f) as u64) << shift;
+        if b & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+
+    Err(Error::InvalidModel(String::from("invalid number")))
+}
+
+// Joint probabilities of the n-grams of a corpus. The file format is the magic,
+// version and order followed by the varint encoded number of distinct n-grams
+// and, for each of them, the delta from the previous index and the count.
+pub struct NgramModel {
+    order: usize,
+    counts: Vec<u32>,
+    total: u64,
+    floor: f32,
+    log_probabilities: Vec<f32>,
+}
+
+impl NgramModel {
+    pub fn train(corpus: &[u8], order: usize) -> Result<NgramModel> {
+        check_order(order)?;
+
+        let mut symbols = Vec::with_capacity(corpus.len());
+        push_symbols(corpus, &mut symbols);
+
+        let mut counts = vec![0u32; SYMBOLS.pow(order as u32)];
+        for window in symbols.windows(order) {
+            let count = &mut counts[index(window)];
+            *count = count.saturating_add(1);
+        }
+
+        NgramModel::from_counts(order, counts)
+    }
+
+    fn from_counts(order: usize, counts: Vec<u32>) -> Result<NgramModel> {
+        let total: u64 = counts.iter().map(|&c| c as u64).sum();
+        if total == 0 {
+            return Err(Error::InvalidModel(String::from("no n-grams")));
+        }
+
+        // Unseen n-grams are taken as a hundred times rarer than one seen once
+        let floor = (0.01 / total as f64).log10() as f32;
+        let log_probabilities = counts.iter().enumerate()
+            .map(|(index, &c)| {
+                let log_probability = if c == 0 { floor } else { (c as f64 / total as f64).log10() as f32 };
+                log_probability + window_log_weight(index, order)
+            })
+            .collect();
+
+        Ok(NgramModel { order, counts, total, floor, log_probabilities })
+    }
+
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    // Geometric mean of the probabilities of the n-grams in the input, which is
+    // enclosed in spaces as if it were a word. Unlike a log-probability it is positive, and
+    // higher is better.
+    pub fn score(&self, bytes: &[u8]) -> f32 {
+        if bytes.is_empty() {
+            return 0.0;
+        }
+
+        let mut symbols = vec![SPACE];
+        push_symbols(bytes, &mut symbols);
+        if symbols.last() != Some(&SPACE) {
+            symbols.push(SPACE);
+        }
+
+        // Shorter input is scored as a whole, by the n-grams it starts
+        if symbols.len() < self.order {
+            let span = SYMBOLS.pow((self.order - symbols.len()) as u32);
+            let start = index(&symbols) * span;
+            let count: u64 = self.counts[start..start + span].iter().map(|&c| c as u64).sum();
+
+            let log_probability = if count == 0 { self.floor } else { (count as f64 / self.total as f64).log10() as f32 };
+            let weight: f32 = symbols.iter().map(|&s| symbol_log_weight(s)).sum();
+            return 10f32.powf(log_probability + weight);
+        }
+
+        let windows = symbols.len() - self.order + 1;
+        let sum: f32 = symbols.windows(self.order)
+            .map(|window| self.log_probabilities[index(window)])
+            .sum();
+
+        10f32.powf(sum / windows as f32)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut result = MAGIC.to_vec();
+        result.push(VERSION);
+        result.push(self.order as u8);
+
+        let seen = self.counts.iter().filter(|&&c| c > 0).count();
+        push_varint(seen as u64, &mut result);
+
+        let mut previous = 0;
+        for (index, &count) in self.counts.iter().enumerate().filter(|&(_, &c)| c > 0) {
+            push_varint((index - previous) as u64, &mut result);
+            push_varint(count as u64, &mut result);
+            previous = index;
+        }
+
+        result
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<NgramModel> {
+        if !bytes.starts_with(MAGIC) {
+            return Err(Error::InvalidModel(String::from("not an n-gram model")));
+        }
+
+        let header = MAGIC.len();
+        match bytes.get(header) {
+            Some(&VERSION) => {}
+            Some(&version) => return Err(Error::InvalidModel(format!("unsupported version {}", version))),
+            None => return Err(Error::InvalidModel(String::from("truncated data"))),
+        }
+
+        let order = *bytes.get(header + 1).ok_or(Error::InvalidModel(String::from("truncated data")))? as usize;
+        check_order(order)?;
+
+        let mut counts = vec![0u32; SYMBOLS.pow(order as u32)];
+        let mut position = header + 2;
+        let seen = read_varint(bytes, &mut position)?;
+
+        let mut index = 0u64;
+        for _ in 0..seen {
+            let delta = read_varint(bytes, &mut position)?;
+            let count = read_varint(bytes, &mut position)?;
+            let invalid = || Error::InvalidModel(format!("invalid n-gram at offset {}", position));
+
+            // A corrupt delta can overflow the index before it is checked
+            index = index.checked_add(delta).ok_or_else(invalid)?;
+            if index >= counts.len() as u64 || count == 0 || count > u32::max_value() as u64 {
+                return Err(invalid());
+            }
+
+            counts[index as usize] = count as u32;
+        }
+
+        if position != bytes.len() {
+            return Err(Error::InvalidModel(format!("unexpected data at offset {}", position)));
+        }
+
+        NgramModel::from_counts(order, counts)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<NgramModel> {
+        NgramModel::from_bytes(&fs::read(path)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        fs::write(path, self.to_bytes())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::process;
+
+    use error::Error;
+    use super::*;
+
+    const CORPUS: &'static str = "\
+It was the best of times, it was the worst of times, it was the age of wisdom,
+it was the age of foolishness, it was the epoch of belief, it was the epoch of
+incredulity, it was the season of Light, it was the season of Darkness, it was
+the spring of hope, it was the winter of despair, we had everything before us,
+we had nothing before us, we were all going direct to Heaven, we were all going
+direct the other way. There were a king with a large jaw and a queen with a
+plain face, on the throne of England; there were a king with a large jaw and a
+queen with a fair face, on the throne of France.";
+
+    #[test]
+    fn english_scores_higher_than_gibberish() {
+        let model = NgramModel::train(CORPUS.as_bytes(), 3).unwrap();
+
+        let english = model.score(b"the king was going to the throne");
+        let gibberish = model.score(b"qzx vjk wqp zzkq xjv qqpz vvkj");
+        let binary = model.score(b"\x01\x8f\x00\x17\xfe\x02\x99\x10");
+
+        assert!(english > gibberish);
+        assert!(gibberish > 0.0);
+        assert!(english > binary);
+        assert!(english > model.score(b"44025<{|({720>{:{+4.5?{4={9"));
+        assert_eq!(model.score(b"The  KING"), model.score(b"the king"));
+    }
+
+    #[test]
+    fn short_input_is_scored() {
+        let model = NgramModel::train(CORPUS.as_bytes(), 4).unwrap();
+        assert!(model.score(b"a") > model.score(b"\x00"));
+        assert_eq!(model.score(b""), 0.0);
+    }
+
+    #[test]
+    fn serialization_round_trip() {
+        let model = NgramModel::train(CORPUS.as_bytes(), 4).unwrap();
+        let loaded = NgramModel::from_bytes(&model.to_bytes()).unwrap();
+
+        assert_eq!(loaded.order(), 4);
+        assert_eq!(loaded.counts, model.counts);
+        assert_eq!(loaded.score(b"it was the age"), model.score(b"it was the age"));
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path = env::temp_dir().join(format!("cryt-ngram-test-{}.model", process::id()));
+        let model = NgramModel::train(CORPUS.as_bytes(), 2).unwrap();
+
+        model.save(&path).unwrap();
+        let loaded = NgramModel::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.unwrap().counts, model.counts);
+    }
+
+    #[test]
+    fn invalid_models() {
+        let model = NgramModel::train(CORPUS.as_bytes(), 2).unwrap().to_bytes();
+        let error = |bytes: &[u8]| NgramModel::from_bytes(bytes).err().unwrap();
+
+        assert_eq!(error(b"NOTAMODEL"), Error::InvalidModel(String::from("not an n-gram model")));
+        assert_eq!(error(&model[..model.len() - 1]), Error::InvalidModel(String::from("truncated data")));
+        assert_eq!(error(b"CRYTNGRM\x02\x02"), Error::InvalidModel(String::from("unsupported version 2")));
+        assert_eq!(error(b"CRYTNGRM\x01\x05\x00"), Error::InvalidModel(String::from("order must be between 1 and 4, got 5")));
+        assert_eq!(error(b"CRYTNGRM\x01\x01\x00"), Error::InvalidModel(String::from("no n-grams")));
+        assert!(NgramModel::train(b"abc", 0).is_err());
+
+        // The second delta overflows the index
+        let overflow = b"CRYTNGRM\x01\x01\x02\x01\x01\xff\xff\xff\xff\xff\xff\xff\xff\xff\x01\x01";
+        assert_eq!(error(overflow), Error::InvalidModel(format!("invalid n-gram at offset {}", overflow.len())));
+    }
+}
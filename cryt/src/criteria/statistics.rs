@@ -0,0 +1,109 @@
+pub fn histogram(bytes: &[u8]) -> [usize; 256] {
+    let mut counts = [0; 256];
+    for &b in bytes {
+        counts[b as usize] += 1;
+    }
+
+    counts
+}
+
+fn counts_entropy(counts: &[usize; 256], length: usize) -> f32 {
+    if length == 0 {
+        return 0.0;
+    }
+
+    counts.iter().filter(|&&c| c > 0).map(|&c| {
+        let p = c as f32 / length as f32;
+        p * (1.0 / p).log2()
+    }).sum()
+}
+
+// Shannon entropy in bits per byte: close to 8 for encrypted or compressed data,
+// around 4 to 5 for english text
+pub fn entropy(bytes: &[u8]) -> f32 {
+    counts_entropy(&histogram(bytes), bytes.len())
+}
+
+// Entropy of each window of the given size, every step bytes, along with its offset.
+// Input shorter than a window is a single window.
+pub fn sliding_entropy(bytes: &[u8], window: usize, step: usize) -> Vec<(usize, f32)> {
+    let window = window.max(1).min(bytes.len());
+    let step = step.max(1);
+    let mut counts = histogram(&bytes[..window]);
+    let mut result = vec![(0, counts_entropy(&counts, window))];
+
+    let mut offset = 0;
+    while offset + step + window <= bytes.len() {
+        // Windows that overlap are updated with the bytes that differ
+        if step < window {
+            for &b in &bytes[offset..offset + step] {
+                counts[b as usize] -= 1;
+            }
+            for &b in &bytes[offset + window..offset + window + step] {
+                counts[b as usize] += 1;
+            }
+        } else {
+            counts = histogram(&bytes[offset + step..offset + step + window]);
+        }
+
+        offset += step;
+        result.push((offset, counts_entropy(&counts, window)));
+    }
+
+    result
+}
+
+// Probability that two bytes taken at random are equal: 1/256 for uniformly random
+// bytes and around 0.06 for english text
+pub fn index_of_coincidence(bytes: &[u8]) -> f32 {
+    if bytes.len() < 2 {
+        return 0.0;
+    }
+
+    let pairs: usize = histogram(bytes).iter().map(|&c| c * c.saturating_sub(1)).sum();
+    pairs as f32 / (bytes.len() * (bytes.len() - 1)) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entropy_of_known_distributions() {
+        let all: Vec<u8> = (0..=255).collect();
+
+        assert_eq!(entropy(b""), 0.0);
+        assert_eq!(entropy(b"aaaa"), 0.0);
+        assert_eq!(entropy(b"abab"), 1.0);
+        assert_eq!(entropy(&all), 8.0);
+    }
+
+    #[test]
+    fn sliding_entropy_windows() {
+        let input = b"aaaaabababababcd";
+
+        assert_eq!(sliding_entropy(input, 4, 4), [(0, 0.0), (4, 1.0), (8, 1.0), (12, 2.0)]);
+        assert_eq!(sliding_entropy(input, 4, 3).len(), 5);
+        assert_eq!(sliding_entropy(input, 2, 6), [(0, 0.0), (6, 1.0), (12, 1.0)]);
+        assert_eq!(sliding_entropy(b"ab", 16, 16), [(0, 1.0)]);
+        assert_eq!(sliding_entropy(b"", 16, 16), [(0, 0.0)]);
+    }
+
+    #[test]
+    fn sliding_entropy_matches_entropy() {
+        let input = b"The quick brown fox jumps over the lazy dog";
+        for (offset, value) in sliding_entropy(input, 10, 3) {
+            assert!((value - entropy(&input[offset..offset + 10])).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn index_of_coincidence_values() {
+        let all: Vec<u8> = (0..=255).collect();
+
+        assert_eq!(index_of_coincidence(b"aaaa"), 1.0);
+        assert_eq!(index_of_coincidence(b"aabb"), 4.0 / 12.0);
+        assert_eq!(index_of_coincidence(&all), 0.0);
+        assert_eq!(index_of_coincidence(b"a"), 0.0);
+    }
+}
@@ -0,0 +1,215 @@
+use error::{Error, Result};
+use super::TrailingLineEnding;
+
+const Z85_SYMBOLS: &'static [u8; 85] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ.-:+=^!/*?&<>()[]{}@%$#";
+
+fn to_base85(group: &[u8]) -> [u8; 5] {
+    let mut value = 0u32;
+    for i in 0..4 {
+        value = value << 8 | *group.get(i).unwrap_or(&0) as u32;
+    }
+
+    let mut digits = [0u8; 5];
+    for digit in digits.iter_mut().rev() {
+        *digit = (value % 85) as u8;
+        value /= 85;
+    }
+
+    digits
+}
+
+// Pushes the bytes of a group of 5 base 85 digits, None if it does not fit in 32 bits
+fn from_base85(digits: &[u8; 5], count: usize, output: &mut Vec<u8>) -> Option<()> {
+    let value = digits.iter().fold(0u64, |acc, &d| acc * 85 + d as u64);
+    if value > u32::max_value() as u64 {
+        return None;
+    }
+
+    for i in 0..count {
+        output.push((value >> (24 - i * 8)) as u8);
+    }
+
+    Some(())
+}
+
+// Adobe/btoa flavour: '!' to 'u', 'z' for a group of zeros and partial final groups
+pub fn ascii85_encode(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(bytes.len() / 4 * 5 + 5);
+    for group in bytes.chunks(4) {
+        if group == [0, 0, 0, 0] {
+            result.push('z');
+            continue;
+        }
+
+        for &digit in to_base85(group).iter().take(group.len() + 1) {
+            result.push((digit + b'!') as char);
+        }
+    }
+
+    result
+}
+
+// Whitespace is ignored and the data may be enclosed in "<~" and "~>"
+pub fn ascii85_decode<T: AsRef<[u8]> + ?Sized>(a85: &T) -> Result<Vec<u8>> {
+    let a85 = a85.as_ref();
+    let start = a85.iter().position(|b| !(*b as char).is_ascii_whitespace()).unwrap_or(0);
+    let start = if a85[start..].starts_with(b"<~") { start + 2 } else { 0 };
+
+    let mut result = Vec::with_capacity(a85.len() / 5 * 4 + 4);
+    let mut digits = [0u8; 5];
+    let mut count = 0;
+    let mut symbols = 0;
+
+    for (offset, &c) in a85.iter().enumerate().skip(start) {
+        if (c as char).is_ascii_whitespace() {
+            continue;
+        }
+
+        if c == b'~' {
+            if a85.get(offset + 1) != Some(&b'>') {
+                return Err(Error::InvalidSymbol { symbol: c, offset });
+            }
+            break;
+        }
+
+        symbols += 1;
+
+        if c == b'z' && count == 0 {
+            result.extend_from_slice(&[0, 0, 0, 0]);
+            continue;
+        }
+
+        if c < b'!' || c > b'u' {
+            return Err(Error::InvalidSymbol { symbol: c, offset });
+        }
+
+        digits[count] = c - b'!';
+        count += 1;
+
+        if count == 5 {
+            from_base85(&digits, 4, &mut result)
+                .ok_or(Error::InvalidSymbol { symbol: c, offset })?;
+            count = 0;
+        }
+    }
+
+    match count {
+        0 => Ok(result),
+        1 => Err(Error::InvalidLength { length: symbols }),
+        _ => {
+            // A partial group is completed with the highest digit
+            for digit in digits.iter_mut().skip(count) {
+                *digit = 84;
+            }
+
+            from_base85(&digits, count - 1, &mut result)
+                .ok_or(Error::InvalidLength { length: symbols })?;
+            Ok(result)
+        }
+    }
+}
+
+// ZeroMQ flavour: the input length must be a multiple of 4
+pub fn z85_encode(bytes: &[u8]) -> Result<String> {
+    if bytes.len() % 4 != 0 {
+        return Err(Error::InvalidLength { length: bytes.len() });
+    }
+
+    let mut result = String::with_capacity(bytes.len() / 4 * 5);
+    for group in bytes.chunks(4) {
+        for &digit in to_base85(group).iter() {
+            result.push(Z85_SYMBOLS[digit as usize] as char);
+        }
+    }
+
+    Ok(result)
+}
+
+pub fn z85_decode<T: AsRef<[u8]> + ?Sized>(z85: &T) -> Result<Vec<u8>> {
+    let mut line_ending = TrailingLineEnding::new();
+    let mut result = Vec::new();
+    let mut digits = [0u8; 5];
+    let mut count = 0;
+    let mut symbols = 0;
+
+    for (offset, &c) in z85.as_ref().iter().enumerate() {
+        if line_ending.skip(c, offset)? {
+            continue;
+        }
+
+        let value = Z85_SYMBOLS.iter().position(|&x| x == c)
+            .ok_or(Error::InvalidSymbol { symbol: c, offset })?;
+
+        symbols += 1;
+        digits[count] = value as u8;
+        count += 1;
+
+        if count == 5 {
+            from_base85(&digits, 4, &mut result)
+                .ok_or(Error::InvalidSymbol { symbol: c, offset })?;
+            count = 0;
+        }
+    }
+
+    if count != 0 {
+        return Err(Error::InvalidLength { length: symbols });
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use error::Error;
+    use super::*;
+
+    #[test]
+    fn ascii85_encoding() {
+        assert_eq!(ascii85_encode(b"Man "), "9jqo^");
+        assert_eq!(ascii85_encode(b"sure."), "F*2M7/c");
+        assert_eq!(ascii85_encode(&[0, 0, 0, 0, 1]), "z!<");
+    }
+
+    #[test]
+    fn ascii85_decoding() {
+        assert_eq!(ascii85_decode("F*2M7/c").unwrap(), b"sure.");
+        assert_eq!(ascii85_decode("z!<").unwrap(), [0, 0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn ascii85_decoding_with_delimiters_and_whitespace() {
+        assert_eq!(ascii85_decode(" <~9jqo^\nF*2M7/c~>\n").unwrap(), b"Man sure.");
+    }
+
+    #[test]
+    fn ascii85_round_trip() {
+        let input: Vec<u8> = (0..=255).collect();
+        for length in 0..9 {
+            let bytes = &input[..input.len() - length];
+            assert_eq!(ascii85_decode(&ascii85_encode(bytes)).unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn ascii85_decoding_errors() {
+        assert_eq!(ascii85_decode("9jqvo"), Err(Error::InvalidSymbol { symbol: b'v', offset: 3 }));
+        assert_eq!(ascii85_decode("9jzqo"), Err(Error::InvalidSymbol { symbol: b'z', offset: 2 }));
+        assert_eq!(ascii85_decode("9jqo^F"), Err(Error::InvalidLength { length: 6 }));
+        assert_eq!(ascii85_decode("uuuuu"), Err(Error::InvalidSymbol { symbol: b'u', offset: 4 }));
+        assert_eq!(ascii85_decode("9jqo^~"), Err(Error::InvalidSymbol { symbol: b'~', offset: 5 }));
+    }
+
+    #[test]
+    fn z85_encoding() {
+        let input = [0x86, 0x4F, 0xD2, 0x6F, 0xB5, 0x59, 0xF7, 0x5B];
+        assert_eq!(z85_encode(&input).unwrap(), "HelloWorld");
+        assert_eq!(z85_encode(&input[..5]), Err(Error::InvalidLength { length: 5 }));
+    }
+
+    #[test]
+    fn z85_decoding() {
+        assert_eq!(z85_decode("HelloWorld\n").unwrap(), [0x86, 0x4F, 0xD2, 0x6F, 0xB5, 0x59, 0xF7, 0x5B]);
+        assert_eq!(z85_decode("HelloWorl"), Err(Error::InvalidLength { length: 9 }));
+        assert_eq!(z85_decode("Hello,orld"), Err(Error::InvalidSymbol { symbol: b',', offset: 5 }));
+    }
+}
@@ -0,0 +1,196 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+use criteria::{Criterion, printable_text};
+use super::Encoding;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chain {
+    pub encodings: Vec<Encoding>,
+    pub payload: Vec<u8>,
+    pub score: f32,
+}
+
+impl Chain {
+    pub fn names(&self) -> Vec<&'static str> {
+        self.encodings.iter().map(|e| e.name()).collect()
+    }
+}
+
+// Encoders of text usually leave a final line ending that should not count against it
+fn trim_trailing_whitespace(bytes: &[u8]) -> &[u8] {
+    let end = bytes.iter().rposition(|b| !(*b as char).is_ascii_whitespace()).map_or(0, |p| p + 1);
+    &bytes[..end]
+}
+
+fn is_text(bytes: &[u8]) -> bool {
+    !bytes.is_empty() && printable_text(bytes) == 1.0
+}
+
+pub struct AutoDecoder {
+    criterion: Box<Criterion>,
+    encodings: Vec<Encoding>,
+    max_depth: usize,
+}
+
+impl AutoDecoder {
+    pub fn new() -> AutoDecoder {
+        AutoDecoder {
+            // Multi-line text must not score below its own encodings
+            criterion: Box::new(printable_text),
+            encodings: Encoding::detectable(),
+            max_depth: 8,
+        }
+    }
+
+    pub fn with_criterion(mut self, criterion: Box<Criterion>) -> AutoDecoder {
+        self.criterion = criterion;
+        self
+    }
+
+    // Candidate encodings, tried in the given order
+    pub fn with_encodings(mut self, encodings: Vec<Encoding>) -> AutoDecoder {
+        self.encodings = encodings;
+        self
+    }
+
+    pub fn with_max_depth(mut self, max_depth: usize) -> AutoDecoder {
+        self.max_depth = max_depth;
+        self
+    }
+
+    // Every chain of decodings found, best first. The input itself is the empty chain.
+    // Equal scores are ranked by depth, since each intermediate layer of an encoded
+    // text is as printable as the text itself, and then by the order of the encodings.
+    pub fn result(&self, input: &[u8]) -> Vec<Chain> {
+        let mut seen = HashSet::new();
+        seen.insert(input.to_vec());
+
+        let root = Chain {
+            encodings: Vec::new(),
+            payload: input.to_vec(),
+            score: self.score(input),
+        };
+
+        let mut chains = Vec::new();
+        self.unwrap(root, &mut seen, &mut chains);
+
+        chains.sort_by(|c1, c2| {
+            c2.score.partial_cmp(&c1.score).unwrap_or(Ordering::Equal)
+                .then(c2.encodings.len().cmp(&c1.encodings.len()))
+        });
+
+        chains
+    }
+
+    fn score(&self, payload: &[u8]) -> f32 {
+        let trimmed = trim_trailing_whitespace(payload);
+        if trimmed.is_empty() {
+            return 0.0;
+        }
+
+        self.criterion.score(trimmed)
+    }
+
+    fn unwrap(&self, chain: Chain, seen: &mut HashSet<Vec<u8>>, chains: &mut Vec<Chain>) {
+        // Only text can hold another layer of encoding
+        if chain.encodings.len() < self.max_depth && is_text(&chain.payload) {
+            for &encoding in self.encodings.iter() {
+                let payload = match encoding.decode(trim_trailing_whitespace(&chain.payload)) {
+                    Ok(payload) => payload,
+                    Err(_) => continue,
+                };
+
+                if payload.is_empty() || !seen.insert(payload.clone()) {
+                    continue;
+                }
+
+                let mut encodings = chain.encodings.clone();
+                encodings.push(encoding);
+                let score = self.score(&payload);
+
+                self.unwrap(Chain { encodings, payload, score }, seen, chains);
+            }
+        }
+
+        chains.push(chain);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use encoding::{base64_encode, hex_encode, Encoding};
+    use super::*;
+
+    #[test]
+    fn single_layer_is_detected() {
+        let input = base64_encode(b"attack at dawn");
+        let chains = AutoDecoder::new().result(input.as_bytes());
+
+        assert_eq!(chains[0].encodings, [Encoding::Base64]);
+        assert_eq!(chains[0].payload, b"attack at dawn");
+        assert_eq!(chains[0].score, 1.0);
+    }
+
+    #[test]
+    fn nested_layers_are_unwrapped() {
+        let inner = base64_encode(b"attack at dawn\n");
+        let middle = hex_encode(inner.as_bytes());
+        let input = base64_encode(middle.as_bytes()) + "\n";
+
+        let chains = AutoDecoder::new().result(input.as_bytes());
+
+        assert_eq!(chains[0].names(), ["base64", "hex", "base64"]);
+        assert_eq!(chains[0].payload, b"attack at dawn\n");
+    }
+
+    #[test]
+    fn multi_line_payload_is_unwrapped() {
+        let text = b"line one\nline two\n\tindented line three\n";
+        let single = base64_encode(text) + "\n";
+        let nested = base64_encode(hex_encode(base64_encode(text).as_bytes()).as_bytes());
+
+        let chains = AutoDecoder::new().result(single.as_bytes());
+        assert_eq!(chains[0].names(), ["base64"]);
+        assert_eq!(chains[0].payload, text.to_vec());
+        assert_eq!(chains[0].score, 1.0);
+
+        let chains = AutoDecoder::new().result(nested.as_bytes());
+        assert_eq!(chains[0].names(), ["base64", "hex", "base64"]);
+        assert_eq!(chains[0].payload, text.to_vec());
+    }
+
+    #[test]
+    fn binary_payload_ends_the_chain() {
+        let input = hex_encode(&[0x00, 0xff, 0x10, 0x80]);
+        let chains = AutoDecoder::new()
+            .with_encodings(vec![Encoding::Hex, Encoding::Base64])
+            .result(input.as_bytes());
+
+        assert_eq!(chains[0].encodings, []);
+        assert!(chains.iter().any(|c| c.encodings == [Encoding::Hex] && c.payload == [0x00, 0xff, 0x10, 0x80]));
+    }
+
+    #[test]
+    fn max_depth_limits_unwrapping() {
+        let input = base64_encode(base64_encode(b"attack at dawn").as_bytes());
+        let chains = AutoDecoder::new()
+            .with_max_depth(1)
+            .result(input.as_bytes());
+
+        assert!(chains.iter().all(|c| c.encodings.len() <= 1));
+        assert_eq!(chains[0].encodings, [Encoding::Base64]);
+    }
+
+    #[test]
+    fn custom_criterion_ranks_chains() {
+        let input = hex_encode(b"XXXX");
+        let criterion = |bytes: &[u8]| bytes.iter().filter(|&&b| b == b'X').count() as f32;
+        let chains = AutoDecoder::new()
+            .with_criterion(Box::new(criterion))
+            .result(input.as_bytes());
+
+        assert_eq!(chains[0].encodings, [Encoding::Hex]);
+        assert_eq!(chains[0].score, 4.0);
+    }
+}
@@ -0,0 +1,217 @@
+use error::{Error, Result};
+use pipeline::Codec;
+use super::{Padding, TrailingLineEnding};
+
+const BASE32_SYMBOLS: [char; 32] = ['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', '2', '3', '4', '5', '6', '7'];
+
+const BASE32_HEX_SYMBOLS: [char; 32] = ['0', '1', '2', '3', '4', '5', '6', '7', '8', '9', 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q', 'R', 'S', 'T', 'U', 'V'];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Base32 {
+    hex_alphabet: bool,
+    padding: Padding,
+}
+
+impl Base32 {
+    // Builder methods
+    pub fn new() -> Base32 {
+        Base32 {
+            hex_alphabet: false,
+            padding: Padding::Required,
+        }
+    }
+
+    // RFC 4648 section 7, "base32hex"
+    pub fn hex() -> Base32 {
+        Base32::new().with_hex_alphabet(true)
+    }
+
+    pub fn with_hex_alphabet(mut self, hex_alphabet: bool) -> Base32 {
+        self.hex_alphabet = hex_alphabet;
+        self
+    }
+
+    pub fn with_padding(mut self, padding: Padding) -> Base32 {
+        self.padding = padding;
+        self
+    }
+
+    // Encoding methods
+    pub fn encode(&self, bytes: &[u8]) -> String {
+        let symbols = self.symbols();
+        let mut result = String::with_capacity(bytes.len() / 5 * 8 + 8);
+        let mut buffer: u16 = 0;
+        let mut bits = 0;
+
+        for &b in bytes {
+            buffer = buffer << 8 | b as u16;
+            bits += 8;
+            while bits >= 5 {
+                bits -= 5;
+                result.push(symbols[(buffer >> bits) as usize & 0x1F]);
+            }
+        }
+
+        if bits > 0 {
+            result.push(symbols[(buffer << (5 - bits)) as usize & 0x1F]);
+        }
+
+        if self.padding != Padding::Omitted {
+            while result.len() % 8 != 0 {
+                result.push('=');
+            }
+        }
+
+        result
+    }
+
+    pub fn decode<T: AsRef<[u8]> + ?Sized>(&self, b32: &T) -> Result<Vec<u8>> {
+        let symbols = self.symbols();
+        let mut result = Vec::new();
+        let mut line_ending = TrailingLineEnding::new();
+        let mut buffer: u16 = 0;
+        let mut bits = 0;
+        let mut length = 0;
+        let mut padding_start = None;
+
+        for (offset, &c) in b32.as_ref().iter().enumerate() {
+            if line_ending.skip(c, offset)? {
+                continue;
+            }
+
+            length += 1;
+
+            if c == b'=' {
+                if self.padding == Padding::Omitted {
+                    return Err(Error::InvalidPadding { offset });
+                }
+
+                padding_start = padding_start.or(Some(length - 1));
+                continue;
+            }
+
+            if padding_start.is_some() {
+                return Err(Error::InvalidPadding { offset });
+            }
+
+            let symbol = (c as char).to_ascii_uppercase();
+            let value = symbols.iter().position(|&x| x == symbol)
+                .ok_or(Error::InvalidSymbol { symbol: c, offset })?;
+
+            buffer = buffer << 5 | value as u16;
+            bits += 5;
+            if bits >= 8 {
+                bits -= 8;
+                result.push((buffer >> bits) as u8);
+            }
+        }
+
+        // Only 2, 4, 5 or 7 symbols can end a quantum
+        let symbols_count = padding_start.unwrap_or(length);
+        match symbols_count % 8 {
+            1 | 3 | 6 => return Err(Error::InvalidLength { length: symbols_count }),
+            _ => {}
+        }
+
+        // Padding only fills the last quantum
+        match padding_start {
+            Some(start) if length != start + (8 - start % 8) % 8 => Err(Error::InvalidPadding { offset: length }),
+            None if length % 8 != 0 && self.padding == Padding::Required => Err(Error::InvalidPadding { offset: length }),
+            _ => Ok(result),
+        }
+    }
+
+    fn symbols(&self) -> &'static [char; 32] {
+        if self.hex_alphabet {
+            &BASE32_HEX_SYMBOLS
+        } else {
+            &BASE32_SYMBOLS
+        }
+    }
+}
+
+impl Codec for Base32 {
+    fn name(&self) -> String {
+        String::from("base32")
+    }
+
+    fn encode(&self, input: &[u8]) -> Result<Vec<u8>> {
+        Ok(Base32::encode(self, input).into_bytes())
+    }
+
+    fn decode(&self, input: &[u8]) -> Result<Vec<u8>> {
+        Base32::decode(self, input)
+    }
+}
+
+pub fn base32_encode(bytes: &[u8]) -> String {
+    Base32::new().encode(bytes)
+}
+
+pub fn base32_decode<T: AsRef<[u8]> + ?Sized>(b32: &T) -> Result<Vec<u8>> {
+    Base32::new().decode(b32)
+}
+
+#[cfg(test)]
+mod tests {
+    use error::Error;
+    use encoding::Padding;
+    use super::*;
+
+    const RFC_4648_VECTORS: [(&'static str, &'static str, &'static str); 7] = [
+        ("", "", ""),
+        ("f", "MY======", "CO======"),
+        ("fo", "MZXQ====", "CPNG===="),
+        ("foo", "MZXW6===", "CPNMU==="),
+        ("foob", "MZXW6YQ=", "CPNMUOG="),
+        ("fooba", "MZXW6YTB", "CPNMUOJ1"),
+        ("foobar", "MZXW6YTBOI======", "CPNMUOJ1E8======"),
+    ];
+
+    #[test]
+    fn base32_encoding() {
+        for &(plain, encoded, _) in RFC_4648_VECTORS.iter() {
+            assert_eq!(base32_encode(plain.as_bytes()), encoded);
+        }
+    }
+
+    #[test]
+    fn base32_decoding() {
+        for &(plain, encoded, _) in RFC_4648_VECTORS.iter() {
+            assert_eq!(base32_decode(encoded).unwrap(), plain.as_bytes());
+        }
+    }
+
+    #[test]
+    fn base32_hex_round_trip() {
+        for &(plain, _, encoded) in RFC_4648_VECTORS.iter() {
+            assert_eq!(Base32::hex().encode(plain.as_bytes()), encoded);
+            assert_eq!(Base32::hex().decode(encoded).unwrap(), plain.as_bytes());
+        }
+    }
+
+    #[test]
+    fn base32_without_padding() {
+        let engine = Base32::new().with_padding(Padding::Omitted);
+        assert_eq!(engine.encode(b"foobar"), "MZXW6YTBOI");
+        assert_eq!(engine.decode("MZXW6YTBOI").unwrap(), b"foobar");
+        assert_eq!(engine.decode("MZXW6YTBOI======"), Err(Error::InvalidPadding { offset: 10 }));
+    }
+
+    #[test]
+    fn base32_decoding_is_case_insensitive() {
+        assert_eq!(base32_decode("mzxw6ytboi======\n").unwrap(), b"foobar");
+    }
+
+    #[test]
+    fn base32_decoding_errors() {
+        assert_eq!(base32_decode("MZXW1YTB"), Err(Error::InvalidSymbol { symbol: b'1', offset: 4 }));
+        assert_eq!(base32_decode("MZXW6YTBOI"), Err(Error::InvalidPadding { offset: 10 }));
+        assert_eq!(base32_decode("MZXW6YTBOI==="), Err(Error::InvalidPadding { offset: 13 }));
+        assert_eq!(base32_decode("MZX====="), Err(Error::InvalidLength { length: 3 }));
+        assert_eq!(base32_decode("MY==A==="), Err(Error::InvalidPadding { offset: 4 }));
+        assert_eq!(base32_decode("MZXW6==========="), Err(Error::InvalidPadding { offset: 16 }));
+        assert_eq!(base32_decode("MZXW6YTB========"), Err(Error::InvalidPadding { offset: 16 }));
+        assert_eq!(base32_decode("========"), Err(Error::InvalidPadding { offset: 8 }));
+    }
+}
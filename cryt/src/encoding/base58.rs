@@ -0,0 +1,150 @@
+use error::{Error, Result};
+use sha256::sha256;
+use super::TrailingLineEnding;
+
+const BASE58_SYMBOLS: [char; 58] = ['1', '2', '3', '4', '5', '6', '7', '8', '9', 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'J', 'K', 'L', 'M', 'N', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'm', 'n', 'o', 'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z'];
+
+const CHECKSUM_LENGTH: usize = 4;
+
+pub fn base58_encode(bytes: &[u8]) -> String {
+    // Each leading zero byte is kept as a leading '1'
+    let zeros = bytes.iter().take_while(|&&b| b == 0).count();
+
+    // Base 58 digits of the remaining big-endian number, least significant first
+    let mut digits: Vec<u8> = Vec::with_capacity(bytes.len() * 138 / 100 + 1);
+    for &b in &bytes[zeros..] {
+        let mut carry = b as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    let mut result = String::with_capacity(zeros + digits.len());
+    for _ in 0..zeros {
+        result.push(BASE58_SYMBOLS[0]);
+    }
+    for &digit in digits.iter().rev() {
+        result.push(BASE58_SYMBOLS[digit as usize]);
+    }
+
+    result
+}
+
+pub fn base58_decode<T: AsRef<[u8]> + ?Sized>(b58: &T) -> Result<Vec<u8>> {
+    let mut line_ending = TrailingLineEnding::new();
+    let mut zeros = 0;
+    let mut leading = true;
+
+    // Base 256 digits of the number, least significant first
+    let mut bytes: Vec<u8> = Vec::new();
+    for (offset, &c) in b58.as_ref().iter().enumerate() {
+        if line_ending.skip(c, offset)? {
+            continue;
+        }
+
+        let value = BASE58_SYMBOLS.iter().position(|&x| x == c as char)
+            .ok_or(Error::InvalidSymbol { symbol: c, offset })?;
+
+        if leading && value == 0 {
+            zeros += 1;
+            continue;
+        }
+        leading = false;
+
+        let mut carry = value as u32;
+        for byte in bytes.iter_mut() {
+            carry += *byte as u32 * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    let mut result = vec![0; zeros];
+    result.extend(bytes.iter().rev());
+    Ok(result)
+}
+
+fn checksum(payload: &[u8]) -> [u8; CHECKSUM_LENGTH] {
+    let digest = sha256(&sha256(payload));
+    [digest[0], digest[1], digest[2], digest[3]]
+}
+
+// Base58 with a double SHA-256 checksum appended, as used by Bitcoin addresses
+pub fn base58check_encode(payload: &[u8]) -> String {
+    let mut bytes = payload.to_vec();
+    bytes.extend_from_slice(&checksum(payload));
+    base58_encode(&bytes)
+}
+
+pub fn base58check_decode<T: AsRef<[u8]> + ?Sized>(b58: &T) -> Result<Vec<u8>> {
+    let mut bytes = base58_decode(b58)?;
+    if bytes.len() < CHECKSUM_LENGTH {
+        return Err(Error::InvalidLength { length: bytes.len() });
+    }
+
+    let payload_length = bytes.len() - CHECKSUM_LENGTH;
+    if checksum(&bytes[..payload_length]) != bytes[payload_length..] {
+        return Err(Error::InvalidChecksum);
+    }
+
+    bytes.truncate(payload_length);
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use error::Error;
+    use encoding::hex_decode;
+    use super::*;
+
+    #[test]
+    fn base58_encoding() {
+        assert_eq!(base58_encode(b"Hello World!"), "2NEpo7TZRRrLZSi2U");
+        assert_eq!(base58_encode(b""), "");
+    }
+
+    #[test]
+    fn base58_encoding_keeps_leading_zeros() {
+        assert_eq!(base58_encode(&[0, 0, 0x28, 0x7f, 0xb4, 0xcd]), "11233QC4");
+        assert_eq!(base58_encode(&[0, 0]), "11");
+    }
+
+    #[test]
+    fn base58_decoding() {
+        assert_eq!(base58_decode("2NEpo7TZRRrLZSi2U").unwrap(), b"Hello World!");
+        assert_eq!(base58_decode("11233QC4\n").unwrap(), [0, 0, 0x28, 0x7f, 0xb4, 0xcd]);
+    }
+
+    #[test]
+    fn base58_decoding_invalid_symbol() {
+        assert_eq!(base58_decode("2NEpo0TZ"), Err(Error::InvalidSymbol { symbol: b'0', offset: 5 }));
+        assert_eq!(base58_decode("2NEpoITZ"), Err(Error::InvalidSymbol { symbol: b'I', offset: 5 }));
+    }
+
+    #[test]
+    fn base58check_bitcoin_address() {
+        let payload = hex_decode("00f54a5851e9372b87810a8e60cdd2e7cfd80b6e31").unwrap();
+        let address = "1PMycacnJaSqwwJqjawXBErnLsZ7RkXUAs";
+
+        assert_eq!(base58check_encode(&payload), address);
+        assert_eq!(base58check_decode(address).unwrap(), payload);
+    }
+
+    #[test]
+    fn base58check_rejects_wrong_checksum() {
+        assert_eq!(base58check_decode("1PMycacnJaSqwwJqjawXBErnLsZ7RkXUAt"), Err(Error::InvalidChecksum));
+        assert_eq!(base58check_decode("2NE"), Err(Error::InvalidLength { length: 2 }));
+    }
+}
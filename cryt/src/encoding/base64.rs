@@ -0,0 +1,583 @@
+use std::io::{self, Read, Write};
+
+use error::{Error, Result};
+use pipeline::Codec;
+use super::{DecodeState, DecodingReader, Padding, TrailingLineEnding};
+
+const BASE64_SYMBOLS: [char; 64] = ['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z', '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '+', '/'];
+
+const BASE64_URL_SAFE_SYMBOLS: [char; 64] = ['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z', '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '-', '_'];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Alphabet {
+    Standard,
+    UrlSafe,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Newline {
+    Lf,
+    CrLf,
+}
+
+impl Newline {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            Newline::Lf => "\n",
+            Newline::CrLf => "\r\n",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Base64 {
+    alphabet: Alphabet,
+    padding: Padding,
+    line_width: Option<usize>,
+    newline: Newline,
+    lenient: bool,
+}
+
+impl Base64 {
+    // Builder methods
+    pub fn new() -> Base64 {
+        Base64 {
+            alphabet: Alphabet::Standard,
+            padding: Padding::Required,
+            line_width: None,
+            newline: Newline::Lf,
+            lenient: false,
+        }
+    }
+
+    // RFC 4648 section 5, as used by JWTs
+    pub fn url_safe() -> Base64 {
+        Base64::new()
+            .with_alphabet(Alphabet::UrlSafe)
+            .with_padding(Padding::Omitted)
+    }
+
+    // RFC 2045, as used by MIME and PEM bodies
+    pub fn mime() -> Base64 {
+        Base64::new()
+            .with_line_width(Some(76))
+            .with_newline(Newline::CrLf)
+            .with_lenient(true)
+    }
+
+    pub fn with_alphabet(mut self, alphabet: Alphabet) -> Base64 {
+        self.alphabet = alphabet;
+        self
+    }
+
+    pub fn with_padding(mut self, padding: Padding) -> Base64 {
+        self.padding = padding;
+        self
+    }
+
+    pub fn with_line_width(mut self, line_width: Option<usize>) -> Base64 {
+        self.line_width = line_width.and_then(|w| if w > 0 { Some(w) } else { None });
+        self
+    }
+
+    pub fn with_newline(mut self, newline: Newline) -> Base64 {
+        self.newline = newline;
+        self
+    }
+
+    // Lenient decoding skips any whitespace and accepts the symbols of both alphabets
+    pub fn with_lenient(mut self, lenient: bool) -> Base64 {
+        self.lenient = lenient;
+        self
+    }
+
+    // Encoding methods
+    pub fn encode(&self, bytes: &[u8]) -> String {
+        let mut result = String::with_capacity(bytes.len() / 3 * 4 + 4);
+        self.encode_to(bytes, &mut 0, &mut result);
+        result
+    }
+
+    pub fn decode<T: AsRef<[u8]> + ?Sized>(&self, b64: &T) -> Result<Vec<u8>> {
+        let b64 = b64.as_ref();
+        let mut result = Vec::with_capacity(b64.len() / 4 * 3);
+        let mut state = Base64DecodeState::new(*self);
+
+        state.update(b64, &mut result)?;
+        state.finish()?;
+
+        Ok(result)
+    }
+
+    fn symbols(&self) -> &'static [char; 64] {
+        match self.alphabet {
+            Alphabet::Standard => &BASE64_SYMBOLS,
+            Alphabet::UrlSafe => &BASE64_URL_SAFE_SYMBOLS,
+        }
+    }
+
+    fn symbol_value(&self, symbol: u8) -> Option<u8> {
+        let symbol = symbol as char;
+        match self.symbols().iter().position(|&x| x == symbol) {
+            Some(value) => Some(value as u8),
+            None if self.lenient => match symbol {
+                '+' | '-' => Some(62),
+                '/' | '_' => Some(63),
+                _ => None,
+            },
+            None => None,
+        }
+    }
+
+    // Appends the encoded bytes to result, wrapping lines starting at the given column
+    fn encode_to(&self, bytes: &[u8], column: &mut usize, result: &mut String) {
+        let symbols = self.symbols();
+        let mut push = |symbol: char| {
+            if let Some(width) = self.line_width {
+                if *column == width {
+                    result.push_str(self.newline.as_str());
+                    *column = 0;
+                }
+            }
+
+            result.push(symbol);
+            *column += 1;
+        };
+
+        let mut remaining: u8 = 0;
+        let mut b64_symbol_index;
+        let mut count = 0;
+
+        for (i, b) in bytes.iter().enumerate() {
+            count += 1;
+            match i % 3 {
+                0 => {
+                    b64_symbol_index = (b >> 2) as usize;
+                    remaining = (b & 0x03) << 4;
+                }
+                1 => {
+                    b64_symbol_index = (remaining | (b >> 4)) as usize;
+                    remaining = (b & 0x0F) << 2;
+                }
+                _ => {
+                    b64_symbol_index = (remaining | (b >> 6)) as usize;
+                    push(symbols[b64_symbol_index]);
+                    b64_symbol_index = (b & 0x3F) as usize;
+                }
+            }
+
+            push(symbols[b64_symbol_index]);
+        }
+
+        let padding = if self.padding == Padding::Omitted { 0 } else { 3 - count % 3 };
+        if count % 3 != 0 {
+            push(symbols[remaining as usize]);
+            for _ in 0..padding {
+                push('=');
+            }
+        }
+    }
+}
+
+impl Codec for Base64 {
+    fn name(&self) -> String {
+        String::from("base64")
+    }
+
+    fn encode(&self, input: &[u8]) -> Result<Vec<u8>> {
+        Ok(Base64::encode(self, input).into_bytes())
+    }
+
+    fn decode(&self, input: &[u8]) -> Result<Vec<u8>> {
+        Base64::decode(self, input)
+    }
+}
+
+pub fn base64_encode(bytes: &[u8]) -> String {
+    Base64::new().encode(bytes)
+}
+
+pub fn base64_decode<T: AsRef<[u8]> + ?Sized>(b64: &T) -> Result<Vec<u8>> {
+    Base64::new().decode(b64)
+}
+
+struct Base64DecodeState {
+    engine: Base64,
+    accumulator: u8,
+    position: usize,
+    padding_start: Option<usize>,
+    offset: usize,
+    end_offset: usize,
+    line_ending: TrailingLineEnding,
+}
+
+impl Base64DecodeState {
+    fn new(engine: Base64) -> Base64DecodeState {
+        Base64DecodeState {
+            engine,
+            accumulator: 0,
+            position: 0,
+            padding_start: None,
+            offset: 0,
+            end_offset: 0,
+            line_ending: TrailingLineEnding::new(),
+        }
+    }
+}
+
+impl DecodeState for Base64DecodeState {
+    fn update(&mut self, input: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        for &c in input {
+            let offset = self.offset;
+            self.offset += 1;
+
+            if self.engine.lenient && (c as char).is_ascii_whitespace() {
+                continue;
+            }
+
+            // Wrapped input may have line endings anywhere
+            if self.engine.line_width.is_some() && (c == b'\n' || c == b'\r') {
+                continue;
+            }
+
+            if self.line_ending.skip(c, offset)? {
+                continue;
+            }
+
+            let position = self.position;
+            self.position += 1;
+            self.end_offset = self.offset;
+
+            if c == b'=' {
+                if self.engine.padding == Padding::Omitted {
+                    return Err(Error::InvalidPadding { offset });
+                }
+
+                // Padding may only fill the last one or two symbols of a quantum
+                let start = self.padding_start.unwrap_or(position);
+                if start % 4 < 2 || position - start >= 2 {
+                    return Err(Error::InvalidPadding { offset });
+                }
+
+                self.padding_start = Some(start);
+                continue;
+            }
+
+            if self.padding_start.is_some() {
+                return Err(Error::InvalidPadding { offset });
+            }
+
+            let value = self.engine.symbol_value(c)
+                .ok_or(Error::InvalidSymbol { symbol: c, offset })?;
+
+            self.accumulator = match position % 4 {
+                0 => value << 2,
+                1 => {
+                    output.push(self.accumulator | (value >> 4));
+                    value << 4
+                }
+                2 => {
+                    output.push(self.accumulator | (value >> 2));
+                    value << 6
+                }
+                _ => {
+                    output.push(self.accumulator | value);
+                    0
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        match (self.position % 4, self.padding_start) {
+            (0, _) => Ok(()),
+            (1, None) => Err(Error::InvalidLength { length: self.position }),
+            (_, None) if self.engine.padding != Padding::Required => Ok(()),
+            _ => Err(Error::InvalidPadding { offset: self.end_offset }),
+        }
+    }
+}
+
+pub struct Base64Encoder<W: Write> {
+    inner: Option<W>,
+    engine: Base64,
+    pending: Vec<u8>,
+    column: usize,
+}
+
+impl<W: Write> Base64Encoder<W> {
+    pub fn new(inner: W) -> Base64Encoder<W> {
+        Base64Encoder {
+            inner: Some(inner),
+            engine: Base64::new(),
+            pending: Vec::with_capacity(3),
+            column: 0,
+        }
+    }
+
+    pub fn with_engine(mut self, engine: Base64) -> Base64Encoder<W> {
+        self.engine = engine;
+        self
+    }
+
+    // Writes the last, possibly padded, quantum
+    pub fn finish(&mut self) -> io::Result<()> {
+        if let Some(ref mut inner) = self.inner {
+            let mut result = String::new();
+            self.engine.encode_to(&self.pending, &mut self.column, &mut result);
+            inner.write_all(result.as_bytes())?;
+            self.pending.clear();
+        }
+
+        Ok(())
+    }
+
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.finish()?;
+        Ok(self.inner.take().unwrap())
+    }
+}
+
+impl<W: Write> Write for Base64Encoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let inner = self.inner.as_mut().unwrap();
+
+        let chunk = &buf[..buf.len().min(super::CHUNK_SIZE)];
+        self.pending.extend_from_slice(chunk);
+
+        // Only complete quanta are encoded, the rest waits for more input
+        let complete = self.pending.len() / 3 * 3;
+        let mut result = String::with_capacity(complete / 3 * 4);
+        self.engine.encode_to(&self.pending[..complete], &mut self.column, &mut result);
+        inner.write_all(result.as_bytes())?;
+        self.pending.drain(..complete);
+
+        Ok(chunk.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.inner {
+            Some(ref mut inner) => inner.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<W: Write> Drop for Base64Encoder<W> {
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
+
+pub struct Base64Decoder<R: Read> {
+    reader: DecodingReader<R, Base64DecodeState>,
+}
+
+impl<R: Read> Base64Decoder<R> {
+    pub fn new(inner: R) -> Base64Decoder<R> {
+        Base64Decoder { reader: DecodingReader::new(inner, Base64DecodeState::new(Base64::new())) }
+    }
+
+    pub fn with_engine(mut self, engine: Base64) -> Base64Decoder<R> {
+        self.reader.state = Base64DecodeState::new(engine);
+        self
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader.into_inner()
+    }
+}
+
+impl<R: Read> Read for Base64Decoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+
+    use error::Error;
+    use super::*;
+    use super::super::tests::OneByteReader;
+
+    #[test]
+    fn base64_encoding_without_padding() {
+        let input = [116, 101, 115, 116, 52, 33];
+        assert_eq!(base64_encode(&input), String::from("dGVzdDQh"))
+    }
+
+    #[test]
+    fn base64_encoding_1_padding() {
+        let input = [116, 101, 115, 116, 49, 48, 52, 33];
+        assert_eq!(base64_encode(&input), String::from("dGVzdDEwNCE="))
+    }
+
+    #[test]
+    fn base64_encoding_2_paddings() {
+        let input = vec![116, 101, 115, 116, 49, 52, 33];
+        assert_eq!(base64_encode(&input), String::from("dGVzdDE0IQ=="))
+    }
+
+    #[test]
+    fn base64_decoding_without_padding() {
+        let input = String::from("dGVzdDQh");
+        assert_eq!(base64_decode(&input).unwrap(), [116, 101, 115, 116, 52, 33]);
+    }
+
+    #[test]
+    fn base64_decoding_1_padding() {
+        let input = String::from("dGVzdDEwNCE=");
+        assert_eq!(base64_decode(&input).unwrap(), vec![116, 101, 115, 116, 49, 48, 52, 33]);
+    }
+
+    #[test]
+    fn base64_decoding_2_paddings() {
+        let input = String::from("dGVzdDE0IQ==");
+        assert_eq!(base64_decode(&input).unwrap(), vec![116, 101, 115, 116, 49, 52, 33]);
+    }
+
+    #[test]
+    fn base64_decoding_invalid_symbol() {
+        assert_eq!(base64_decode("dGV*dDQh"), Err(Error::InvalidSymbol { symbol: b'*', offset: 3 }));
+    }
+
+    #[test]
+    fn base64_decoding_ignores_trailing_line_ending() {
+        assert_eq!(base64_decode("dGVzdDQh\n").unwrap(), [116, 101, 115, 116, 52, 33]);
+    }
+
+    #[test]
+    fn base64_decoding_missing_padding() {
+        assert_eq!(base64_decode("dGVzdDE0IQ"), Err(Error::InvalidPadding { offset: 10 }));
+        assert_eq!(base64_decode("dGVzdDE0IQ="), Err(Error::InvalidPadding { offset: 11 }));
+    }
+
+    #[test]
+    fn base64_decoding_misplaced_padding() {
+        assert_eq!(base64_decode("dGVz=DQh"), Err(Error::InvalidPadding { offset: 4 }));
+        assert_eq!(base64_decode("dGVzdA=h"), Err(Error::InvalidPadding { offset: 7 }));
+        assert_eq!(base64_decode("dGVzdA======"), Err(Error::InvalidPadding { offset: 8 }));
+    }
+
+    #[test]
+    fn base64_decoding_invalid_length() {
+        assert_eq!(base64_decode("dGVzd"), Err(Error::InvalidLength { length: 5 }));
+    }
+
+    #[test]
+    fn base64_encoder_keeps_incomplete_quanta() {
+        let mut encoder = Base64Encoder::new(Vec::new());
+        for &b in [116, 101, 115, 116, 49, 52, 33].iter() {
+            encoder.write_all(&[b]).unwrap();
+        }
+
+        assert_eq!(encoder.into_inner().unwrap(), b"dGVzdDE0IQ==");
+    }
+
+    #[test]
+    fn base64_encoder_finishes_on_drop() {
+        let mut output = Vec::new();
+        {
+            let mut encoder = Base64Encoder::new(&mut output);
+            encoder.write_all(&[116, 101, 115, 116, 49, 48, 52, 33]).unwrap();
+        }
+
+        assert_eq!(output, b"dGVzdDEwNCE=");
+    }
+
+    #[test]
+    fn base64_decoder_reads_byte_by_byte() {
+        let mut decoder = Base64Decoder::new(OneByteReader::new(b"dGVzdDEwNCE=\r\n"));
+        let mut result = Vec::new();
+        decoder.read_to_end(&mut result).unwrap();
+
+        assert_eq!(result, [116, 101, 115, 116, 49, 48, 52, 33]);
+    }
+
+    #[test]
+    fn base64_decoder_reports_missing_padding() {
+        let mut decoder = Base64Decoder::new(&b"dGVzdDE0IQ"[..]);
+        let error = decoder.read_to_end(&mut Vec::new()).unwrap_err();
+
+        assert_eq!(error.to_string(), "invalid padding at offset 10");
+    }
+
+    #[test]
+    fn url_safe_encoding_without_padding() {
+        let input = [0xfb, 0xff, 0xbf, 0x3e];
+        assert_eq!(Base64::new().encode(&input), "+/+/Pg==");
+        assert_eq!(Base64::url_safe().encode(&input), "-_-_Pg");
+    }
+
+    #[test]
+    fn url_safe_decoding_without_padding() {
+        assert_eq!(Base64::url_safe().decode("-_-_Pg").unwrap(), [0xfb, 0xff, 0xbf, 0x3e]);
+        assert_eq!(Base64::url_safe().decode("-_-_Pg=="), Err(Error::InvalidPadding { offset: 6 }));
+        assert_eq!(Base64::url_safe().decode("+/+/Pg"), Err(Error::InvalidSymbol { symbol: b'+', offset: 0 }));
+    }
+
+    #[test]
+    fn optional_padding_decoding() {
+        let engine = Base64::new().with_padding(Padding::Optional);
+        assert_eq!(engine.decode("dGVzdDE0IQ==").unwrap(), [116, 101, 115, 116, 49, 52, 33]);
+        assert_eq!(engine.decode("dGVzdDE0IQ").unwrap(), [116, 101, 115, 116, 49, 52, 33]);
+        assert_eq!(engine.decode("dGVzdDE0IQ="), Err(Error::InvalidPadding { offset: 11 }));
+    }
+
+    #[test]
+    fn mime_encoding_wraps_lines() {
+        let input = [0; 60];
+        let encoded = Base64::mime().encode(&input);
+
+        assert_eq!(encoded, "A".repeat(76) + "\r\n" + &"A".repeat(4));
+    }
+
+    #[test]
+    fn mime_decoding_skips_whitespace() {
+        let input = " dGVz\r\n\tdDE0\r\nIQ== \n";
+        assert_eq!(Base64::mime().decode(input).unwrap(), [116, 101, 115, 116, 49, 52, 33]);
+    }
+
+    #[test]
+    fn strict_decoding_rejects_whitespace() {
+        assert_eq!(base64_decode("dGVz dDQh"), Err(Error::InvalidSymbol { symbol: b' ', offset: 4 }));
+    }
+
+    #[test]
+    fn lenient_decoding_accepts_both_alphabets() {
+        let engine = Base64::new().with_lenient(true);
+        assert_eq!(engine.decode("+/-_Pg==").unwrap(), [0xfb, 0xff, 0xbf, 0x3e]);
+    }
+
+    #[test]
+    fn wrapped_decoding_accepts_line_endings() {
+        let engine = Base64::new().with_line_width(Some(4));
+        assert_eq!(engine.encode(&[116, 101, 115, 116, 52, 33]), "dGVz\ndDQh");
+        assert_eq!(engine.decode("dGVz\ndDQh\n").unwrap(), [116, 101, 115, 116, 52, 33]);
+    }
+
+    #[test]
+    fn base64_encoder_wraps_across_writes() {
+        let engine = Base64::new().with_line_width(Some(4)).with_newline(Newline::CrLf);
+        let mut encoder = Base64Encoder::new(Vec::new()).with_engine(engine);
+        for &b in [116, 101, 115, 116, 49, 52, 33].iter() {
+            encoder.write_all(&[b]).unwrap();
+        }
+
+        assert_eq!(encoder.into_inner().unwrap(), &b"dGVz\r\ndDE0\r\nIQ=="[..]);
+    }
+
+    #[test]
+    fn base64_decoder_with_url_safe_engine() {
+        let mut decoder = Base64Decoder::new(OneByteReader::new(b"-_-_Pg"))
+            .with_engine(Base64::url_safe());
+        let mut result = Vec::new();
+        decoder.read_to_end(&mut result).unwrap();
+
+        assert_eq!(result, [0xfb, 0xff, 0xbf, 0x3e]);
+    }
+}
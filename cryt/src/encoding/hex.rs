@@ -0,0 +1,211 @@
+use std::io::{self, Read, Write};
+
+use error::{Error, Result};
+use super::{DecodeState, DecodingReader, TrailingLineEnding};
+
+const HEX_SYMBOLS: [char; 16] = ['0', '1', '2', '3', '4', '5', '6', '7', '8', '9', 'a', 'b', 'c', 'd', 'e', 'f'];
+
+pub fn hex_encode(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        result.push(HEX_SYMBOLS[(byte >> 4) as usize]);
+        result.push(HEX_SYMBOLS[(byte & 0x0F) as usize]);
+    }
+
+    result
+}
+
+fn hex_nyble(symbol: u8) -> Option<u8> {
+    let symbol = (symbol as char).to_ascii_lowercase();
+    HEX_SYMBOLS.iter().position(|&x| x == symbol).map(|p| p as u8)
+}
+
+struct HexDecodeState {
+    upper_nyble: Option<u8>,
+    symbols: usize,
+    offset: usize,
+    line_ending: TrailingLineEnding,
+}
+
+impl HexDecodeState {
+    fn new() -> HexDecodeState {
+        HexDecodeState {
+            upper_nyble: None,
+            symbols: 0,
+            offset: 0,
+            line_ending: TrailingLineEnding::new(),
+        }
+    }
+}
+
+impl DecodeState for HexDecodeState {
+    fn update(&mut self, input: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        for &c in input {
+            let offset = self.offset;
+            self.offset += 1;
+
+            if self.line_ending.skip(c, offset)? {
+                continue;
+            }
+
+            let nyble = hex_nyble(c)
+                .ok_or(Error::InvalidSymbol { symbol: c, offset })?;
+
+            self.upper_nyble = match self.upper_nyble {
+                Some(upper_nyble) => {
+                    output.push(upper_nyble << 4 | nyble);
+                    None
+                }
+                None => Some(nyble),
+            };
+            self.symbols += 1;
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if self.upper_nyble.is_some() {
+            return Err(Error::InvalidLength { length: self.symbols });
+        }
+
+        Ok(())
+    }
+}
+
+pub fn hex_decode<T: AsRef<[u8]> + ?Sized>(hex: &T) -> Result<Vec<u8>> {
+    let hex = hex.as_ref();
+    let mut result = Vec::with_capacity(hex.len() / 2);
+    let mut state = HexDecodeState::new();
+
+    state.update(hex, &mut result)?;
+    state.finish()?;
+
+    Ok(result)
+}
+
+pub struct HexEncoder<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> HexEncoder<W> {
+    pub fn new(inner: W) -> HexEncoder<W> {
+        HexEncoder { inner }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for HexEncoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let chunk = &buf[..buf.len().min(super::CHUNK_SIZE)];
+        self.inner.write_all(hex_encode(chunk).as_bytes())?;
+        Ok(chunk.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+pub struct HexDecoder<R: Read> {
+    reader: DecodingReader<R, HexDecodeState>,
+}
+
+impl<R: Read> HexDecoder<R> {
+    pub fn new(inner: R) -> HexDecoder<R> {
+        HexDecoder { reader: DecodingReader::new(inner, HexDecodeState::new()) }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader.into_inner()
+    }
+}
+
+impl<R: Read> Read for HexDecoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Read, Write};
+
+    use error::Error;
+    use super::*;
+    use super::super::tests::OneByteReader;
+
+    #[test]
+    fn hex_encoding() {
+        let input = [10, 123, 232, 100];
+        assert_eq!(hex_encode(&input), String::from("0a7be864"));
+    }
+
+    #[test]
+    fn hex_decoding() {
+        let input = String::from("0a7be864");
+        assert_eq!(hex_decode(&input).unwrap(), [10, 123, 232, 100]);
+    }
+
+    #[test]
+    fn hex_decoding_accepts_str_bytes_and_uppercase() {
+        assert_eq!(hex_decode("0A7BE864").unwrap(), [10, 123, 232, 100]);
+        assert_eq!(hex_decode(&b"0a7be864"[..]).unwrap(), [10, 123, 232, 100]);
+    }
+
+    #[test]
+    fn hex_decoding_ignores_trailing_line_ending() {
+        assert_eq!(hex_decode("0a7b\r\n").unwrap(), [10, 123]);
+    }
+
+    #[test]
+    fn hex_decoding_invalid_symbol() {
+        assert_eq!(hex_decode("0a7g"), Err(Error::InvalidSymbol { symbol: b'g', offset: 3 }));
+        assert_eq!(hex_decode("0a\n7b"), Err(Error::InvalidSymbol { symbol: b'\n', offset: 2 }));
+    }
+
+    #[test]
+    fn hex_decoding_odd_length() {
+        assert_eq!(hex_decode("0a7"), Err(Error::InvalidLength { length: 3 }));
+        assert_eq!(hex_decode("0a7\n"), Err(Error::InvalidLength { length: 3 }));
+    }
+
+    #[test]
+    fn hex_encoder_writes_chunks() {
+        let mut encoder = HexEncoder::new(Vec::new());
+        encoder.write_all(&[10, 123]).unwrap();
+        encoder.write_all(&[232]).unwrap();
+        encoder.write_all(&[100]).unwrap();
+
+        assert_eq!(encoder.into_inner(), b"0a7be864");
+    }
+
+    #[test]
+    fn hex_decoder_reads_byte_by_byte() {
+        let mut decoder = HexDecoder::new(OneByteReader::new(b"0a7be864\n"));
+        let mut result = Vec::new();
+        decoder.read_to_end(&mut result).unwrap();
+
+        assert_eq!(result, [10, 123, 232, 100]);
+    }
+
+    #[test]
+    fn hex_decoder_reports_invalid_symbol() {
+        let mut decoder = HexDecoder::new(&b"0a7bz864"[..]);
+        let error = decoder.read_to_end(&mut Vec::new()).unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), "invalid symbol 'z' at offset 4");
+    }
+
+    #[test]
+    fn hex_decoder_reports_odd_length() {
+        let mut decoder = HexDecoder::new(&b"0a7be86"[..]);
+        let error = decoder.read_to_end(&mut Vec::new()).unwrap_err();
+
+        assert_eq!(error.to_string(), "invalid input length 7");
+    }
+}
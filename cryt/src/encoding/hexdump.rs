@@ -0,0 +1,333 @@
+use std::fmt::Write;
+
+use error::{Error, Result};
+use pipeline::Codec;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HexdumpStyle {
+    // "00000000: 6865 6c6c 6f0a  hello."
+    Xxd,
+    // "00000000  68 65 6c 6c 6f 0a  |hello.|"
+    Canonical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hexdump {
+    style: HexdumpStyle,
+    width: usize,
+    group: usize,
+    uppercase: bool,
+}
+
+fn ascii(byte: u8) -> char {
+    if byte >= 0x20 && byte <= 0x7e {
+        byte as char
+    } else {
+        '.'
+    }
+}
+
+impl Hexdump {
+    // Builder methods
+    pub fn new() -> Hexdump {
+        Hexdump {
+            style: HexdumpStyle::Xxd,
+            width: 16,
+            group: 2,
+            uppercase: false,
+        }
+    }
+
+    // As printed by hexdump -C
+    pub fn canonical() -> Hexdump {
+        Hexdump::new()
+            .with_style(HexdumpStyle::Canonical)
+            .with_group(1)
+    }
+
+    pub fn with_style(mut self, style: HexdumpStyle) -> Hexdump {
+        self.style = style;
+        self
+    }
+
+    // Bytes per line
+    pub fn with_width(mut self, width: usize) -> Hexdump {
+        self.width = width.max(1);
+        self
+    }
+
+    // Bytes per group of hex digits, only used by the xxd style
+    pub fn with_group(mut self, group: usize) -> Hexdump {
+        self.group = group.max(1);
+        self
+    }
+
+    pub fn with_uppercase(mut self, uppercase: bool) -> Hexdump {
+        self.uppercase = uppercase;
+        self
+    }
+
+    // Encoding methods
+    pub fn encode(&self, bytes: &[u8]) -> String {
+        let mut result = String::new();
+        for (i, line) in bytes.chunks(self.width).enumerate() {
+            match self.style {
+                HexdumpStyle::Xxd => self.xxd_line(i * self.width, line, &mut result),
+                HexdumpStyle::Canonical => self.canonical_line(i * self.width, line, &mut result),
+            }
+        }
+
+        // hexdump -C ends with the total length
+        if self.style == HexdumpStyle::Canonical && !bytes.is_empty() {
+            writeln!(result, "{:08x}", bytes.len()).unwrap();
+        }
+
+        result
+    }
+
+    pub fn decode<T: AsRef<[u8]> + ?Sized>(&self, dump: &T) -> Result<Vec<u8>> {
+        hexdump_decode(dump)
+    }
+
+    fn push_hex(&self, byte: u8, result: &mut String) {
+        if self.uppercase {
+            write!(result, "{:02X}", byte).unwrap();
+        } else {
+            write!(result, "{:02x}", byte).unwrap();
+        }
+    }
+
+    fn xxd_line(&self, offset: usize, line: &[u8], result: &mut String) {
+        write!(result, "{:08x}: ", offset).unwrap();
+
+        for group in line.chunks(self.group) {
+            for &b in group {
+                self.push_hex(b, result);
+            }
+            result.push(' ');
+        }
+
+        // Short lines are padded so that the ascii column stays aligned
+        let groups = |bytes: usize| (bytes + self.group - 1) / self.group;
+        let missing = (self.width - line.len()) * 2 + groups(self.width) - groups(line.len());
+        for _ in 0..missing + 1 {
+            result.push(' ');
+        }
+
+        result.extend(line.iter().map(|&b| ascii(b)));
+        result.push('\n');
+    }
+
+    fn canonical_line(&self, offset: usize, line: &[u8], result: &mut String) {
+        write!(result, "{:08x}  ", offset).unwrap();
+
+        for i in 0..self.width {
+            match line.get(i) {
+                Some(&b) => {
+                    self.push_hex(b, result);
+                    result.push(' ');
+                }
+                None => result.push_str("   "),
+            }
+
+            if i + 1 == self.width / 2 {
+                result.push(' ');
+            }
+        }
+
+        result.push_str(" |");
+        result.extend(line.iter().map(|&b| ascii(b)));
+        result.push_str("|\n");
+    }
+}
+
+impl Codec for Hexdump {
+    fn name(&self) -> String {
+        String::from("hexdump")
+    }
+
+    fn encode(&self, input: &[u8]) -> Result<Vec<u8>> {
+        Ok(Hexdump::encode(self, input).into_bytes())
+    }
+
+    fn decode(&self, input: &[u8]) -> Result<Vec<u8>> {
+        Hexdump::decode(self, input)
+    }
+}
+
+fn is_hex_digit(b: u8) -> bool {
+    (b as char).is_ascii_hexdigit()
+}
+
+fn hex_value(digits: &[u8]) -> usize {
+    digits.iter().fold(0, |acc, &d| acc << 4 | (d as char).to_digit(16).unwrap() as usize)
+}
+
+fn syntax_error<T>(offset: usize, message: &str) -> Result<T> {
+    Err(Error::Syntax { offset, message: String::from(message) })
+}
+
+// Reads back dumps in both the xxd and hexdump -C styles, including the '*'
+// lines hexdump uses in place of repeated lines
+pub fn hexdump_decode<T: AsRef<[u8]> + ?Sized>(dump: &T) -> Result<Vec<u8>> {
+    let mut result = Vec::new();
+    let mut previous: Vec<u8> = Vec::new();
+    let mut squeezed = false;
+    let mut line_start = 0;
+
+    for line in dump.as_ref().split(|&b| b == b'\n') {
+        let start = line_start;
+        line_start += line.len() + 1;
+
+        let skipped = line.iter().take_while(|&&b| b == b' ' || b == b'\t').count();
+        let line = &line[skipped..];
+        let start = start + skipped;
+
+        if line.iter().all(|&b| (b as char).is_ascii_whitespace()) {
+            continue;
+        }
+
+        if line[0] == b'*' {
+            squeezed = true;
+            continue;
+        }
+
+        let offset_length = line.iter().take_while(|&&b| is_hex_digit(b)).count();
+        if offset_length == 0 {
+            return Err(Error::InvalidSymbol { symbol: line[0], offset: start });
+        }
+
+        let offset = hex_value(&line[..offset_length]);
+        if squeezed && !previous.is_empty() {
+            while result.len() < offset {
+                result.extend_from_slice(&previous);
+            }
+            result.truncate(offset);
+            squeezed = false;
+        }
+
+        if offset != result.len() {
+            return syntax_error(start, "unexpected offset");
+        }
+
+        // The hex column ends at the ascii column: two spaces in the xxd style, '|' in hexdump's
+        let rest = &line[offset_length..];
+        let (hex_start, hex_end) = if rest.first() == Some(&b':') {
+            let end = rest.windows(2).position(|w| w == b"  ").unwrap_or(rest.len());
+            (1, end.max(1))
+        } else {
+            (0, rest.iter().position(|&b| b == b'|').unwrap_or(rest.len()))
+        };
+
+        previous.clear();
+        let mut position = hex_start;
+        for token in rest[hex_start..hex_end].split(|&b| (b as char).is_ascii_whitespace()) {
+            let token_offset = start + offset_length + position;
+            position += token.len() + 1;
+
+            if token.is_empty() {
+                continue;
+            }
+
+            if let Some(i) = token.iter().position(|&b| !is_hex_digit(b)) {
+                return Err(Error::InvalidSymbol { symbol: token[i], offset: token_offset + i });
+            }
+
+            if token.len() % 2 != 0 {
+                return syntax_error(token_offset, "odd number of hex digits");
+            }
+
+            for digits in token.chunks(2) {
+                previous.push(hex_value(digits) as u8);
+            }
+        }
+
+        result.extend_from_slice(&previous);
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use error::Error;
+    use super::*;
+
+    const INPUT: &'static [u8] = b"Hello, world!\nThis is a hexdump test\x00\x01\xff";
+
+    const XXD: &'static str = "\
+00000000: 4865 6c6c 6f2c 2077 6f72 6c64 210a 5468  Hello, world!.Th\n\
+00000010: 6973 2069 7320 6120 6865 7864 756d 7020  is is a hexdump \n\
+00000020: 7465 7374 0001 ff                        test...\n";
+
+    const CANONICAL: &'static str = "\
+00000000  48 65 6c 6c 6f 2c 20 77  6f 72 6c 64 21 0a 54 68  |Hello, world!.Th|\n\
+00000010  69 73 20 69 73 20 61 20  68 65 78 64 75 6d 70 20  |is is a hexdump |\n\
+00000020  74 65 73 74 00 01 ff                              |test...|\n\
+00000027\n";
+
+    #[test]
+    fn xxd_style() {
+        assert_eq!(Hexdump::new().encode(INPUT), XXD);
+    }
+
+    #[test]
+    fn canonical_style() {
+        assert_eq!(Hexdump::canonical().encode(INPUT), CANONICAL);
+    }
+
+    #[test]
+    fn custom_width_group_and_case() {
+        let dump = Hexdump::new()
+            .with_width(8)
+            .with_group(4)
+            .with_uppercase(true)
+            .encode(&INPUT[..12]);
+
+        assert_eq!(dump, "00000000: 48656C6C 6F2C2077  Hello, w\n00000008: 6F726C64           orld\n");
+    }
+
+    #[test]
+    fn decoding_both_styles() {
+        assert_eq!(hexdump_decode(XXD).unwrap(), INPUT);
+        assert_eq!(hexdump_decode(CANONICAL).unwrap(), INPUT);
+    }
+
+    #[test]
+    fn decoding_round_trip() {
+        let input: Vec<u8> = (0..=255).collect();
+        let dump = Hexdump::new().with_width(7).with_group(3).encode(&input);
+
+        assert_eq!(hexdump_decode(&dump).unwrap(), input);
+    }
+
+    #[test]
+    fn decoding_ascii_column_with_separators() {
+        let dump = "00000000: 7c20 2020 7c  |   |\n";
+        assert_eq!(hexdump_decode(dump).unwrap(), b"|   |");
+    }
+
+    #[test]
+    fn decoding_squeezed_lines() {
+        let dump = "\
+00000000  00 00 00 00 00 00 00 00  00 00 00 00 00 00 00 00  |................|
+*
+00000030  41 42                                             |AB|
+00000032
+";
+        let mut expected = vec![0; 48];
+        expected.extend_from_slice(b"AB");
+
+        assert_eq!(hexdump_decode(dump).unwrap(), expected);
+    }
+
+    #[test]
+    fn decoding_errors() {
+        assert_eq!(hexdump_decode("00000000: 48zz\n"), Err(Error::InvalidSymbol { symbol: b'z', offset: 12 }));
+        assert_eq!(hexdump_decode("00000010: 4865\n"),
+                   Err(Error::Syntax { offset: 0, message: String::from("unexpected offset") }));
+        assert_eq!(hexdump_decode("00000000: 4865\n  xyz"), Err(Error::InvalidSymbol { symbol: b'x', offset: 17 }));
+        assert_eq!(hexdump_decode("00000000: 486\n"),
+                   Err(Error::Syntax { offset: 10, message: String::from("odd number of hex digits") }));
+    }
+}
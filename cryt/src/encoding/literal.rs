@@ -0,0 +1,411 @@
+use std::fmt::Write;
+
+use error::{Error, Result};
+use pipeline::Codec;
+use super::TrailingLineEnding;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LiteralStyle {
+    // { 0x48, 0x69, 0x0a }
+    C,
+    // b"Hi\n"
+    Rust,
+    // b"Hi\n", python also accepts single quotes and octal escapes
+    Python,
+    // Hi\n, as understood by printf and echo -e
+    Escaped,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Literal {
+    style: LiteralStyle,
+    escape_all: bool,
+}
+
+fn syntax_error<T>(offset: usize, message: &str) -> Result<T> {
+    Err(Error::Syntax { offset, message: String::from(message) })
+}
+
+fn is_whitespace(b: u8) -> bool {
+    (b as char).is_ascii_whitespace()
+}
+
+fn skip_whitespace(input: &[u8], position: usize) -> usize {
+    position + input[position..].iter().take_while(|&&b| is_whitespace(b)).count()
+}
+
+fn digit_value(digit: u8, radix: u32, offset: usize) -> Result<u32> {
+    (digit as char).to_digit(radix).ok_or(Error::InvalidSymbol { symbol: digit, offset })
+}
+
+impl Literal {
+    // Builder methods
+    pub fn new() -> Literal {
+        Literal {
+            style: LiteralStyle::Escaped,
+            escape_all: false,
+        }
+    }
+
+    pub fn c() -> Literal {
+        Literal::new().with_style(LiteralStyle::C)
+    }
+
+    pub fn rust() -> Literal {
+        Literal::new().with_style(LiteralStyle::Rust)
+    }
+
+    pub fn python() -> Literal {
+        Literal::new().with_style(LiteralStyle::Python)
+    }
+
+    pub fn with_style(mut self, style: LiteralStyle) -> Literal {
+        self.style = style;
+        self
+    }
+
+    // Write every byte as \xNN instead of keeping printable characters
+    pub fn with_escape_all(mut self, escape_all: bool) -> Literal {
+        self.escape_all = escape_all;
+        self
+    }
+
+    // Encoding methods
+    pub fn encode(&self, bytes: &[u8]) -> String {
+        match self.style {
+            LiteralStyle::C => {
+                if bytes.is_empty() {
+                    return String::from("{}");
+                }
+
+                let elements: Vec<String> = bytes.iter().map(|b| format!("0x{:02x}", b)).collect();
+                format!("{{ {} }}", elements.join(", "))
+            }
+            LiteralStyle::Rust | LiteralStyle::Python => {
+                let mut result = String::from("b\"");
+                self.escape_to(bytes, &mut result);
+                result.push('"');
+                result
+            }
+            LiteralStyle::Escaped => {
+                let mut result = String::new();
+                self.escape_to(bytes, &mut result);
+                result
+            }
+        }
+    }
+
+    pub fn decode<T: AsRef<[u8]> + ?Sized>(&self, literal: &T) -> Result<Vec<u8>> {
+        let literal = literal.as_ref();
+        let mut result = Vec::new();
+
+        match self.style {
+            LiteralStyle::C => decode_c(literal, &mut result)?,
+            LiteralStyle::Rust | LiteralStyle::Python => self.decode_quoted(literal, &mut result)?,
+            LiteralStyle::Escaped => {
+                self.unescape(literal, 0, None, &mut result)?;
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn escape_to(&self, bytes: &[u8], result: &mut String) {
+        for &b in bytes {
+            match b {
+                _ if self.escape_all => write!(result, "\\x{:02x}", b).unwrap(),
+                b'\\' => result.push_str("\\\\"),
+                b'"' if self.style != LiteralStyle::Escaped => result.push_str("\\\""),
+                b'\n' => result.push_str("\\n"),
+                b'\r' => result.push_str("\\r"),
+                b'\t' => result.push_str("\\t"),
+                0x20..=0x7e => result.push(b as char),
+                _ => write!(result, "\\x{:02x}", b).unwrap(),
+            }
+        }
+    }
+
+    // One b"..." literal, or for python any number of adjacent ones
+    fn decode_quoted(&self, input: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        let python = self.style == LiteralStyle::Python;
+        let mut position = skip_whitespace(input, 0);
+        let mut literals = 0;
+
+        while position < input.len() {
+            if literals > 0 && !python {
+                return Err(Error::InvalidSymbol { symbol: input[position], offset: position });
+            }
+
+            match input[position] {
+                b'b' => {}
+                b'B' if python => {}
+                _ => return syntax_error(position, "expected byte string"),
+            }
+
+            position += 1;
+            let quote = match input.get(position) {
+                Some(&b'"') => b'"',
+                Some(&b'\'') if python => b'\'',
+                _ => return syntax_error(position, "expected quote"),
+            };
+
+            position = self.unescape(input, position + 1, Some(quote), output)?;
+            position = skip_whitespace(input, position);
+            literals += 1;
+        }
+
+        if literals == 0 {
+            return syntax_error(position, "expected byte string");
+        }
+
+        Ok(())
+    }
+
+    // Pushes the bytes up to the closing quote, or the end of the input if there is
+    // none, and returns the position that follows
+    fn unescape(&self, input: &[u8], start: usize, quote: Option<u8>, output: &mut Vec<u8>) -> Result<usize> {
+        let rust = self.style == LiteralStyle::Rust;
+        let mut line_ending = TrailingLineEnding::new();
+        let mut position = start;
+
+        while position < input.len() {
+            let c = input[position];
+
+            if Some(c) == quote {
+                return Ok(position + 1);
+            }
+
+            if quote.is_none() && line_ending.skip(c, position)? {
+                position += 1;
+                continue;
+            }
+
+            if c != b'\\' {
+                output.push(c);
+                position += 1;
+                continue;
+            }
+
+            let escape = match input.get(position + 1) {
+                Some(&escape) => escape,
+                None => return syntax_error(position, "incomplete escape"),
+            };
+
+            let value = match escape {
+                b'x' => {
+                    if input.len() < position + 4 {
+                        return syntax_error(position, "incomplete escape");
+                    }
+
+                    let high = digit_value(input[position + 2], 16, position + 2)?;
+                    let low = digit_value(input[position + 3], 16, position + 3)?;
+                    position += 4;
+                    (high << 4 | low) as u8
+                }
+                b'0'..=b'7' if !rust => {
+                    let digits = input[position + 1..].iter().take(3).take_while(|&&d| d >= b'0' && d <= b'7').count();
+                    let value = input[position + 1..position + 1 + digits].iter()
+                        .fold(0, |acc, &d| acc * 8 + (d - b'0') as u32);
+
+                    if value > 0xff {
+                        return syntax_error(position, "value out of range");
+                    }
+
+                    position += 1 + digits;
+                    value as u8
+                }
+                b'\n' => {
+                    // Line continuation, rust also skips the indentation that follows
+                    position += 2;
+                    if rust {
+                        position = skip_whitespace(input, position);
+                    }
+                    continue;
+                }
+                _ => {
+                    let value = match escape {
+                        b'n' => b'\n',
+                        b'r' => b'\r',
+                        b't' => b'\t',
+                        b'\\' | b'"' | b'\'' => escape,
+                        b'0' => 0,
+                        b'a' if !rust => 0x07,
+                        b'b' if !rust => 0x08,
+                        b'f' if !rust => 0x0c,
+                        b'v' if !rust => 0x0b,
+                        b'e' if self.style == LiteralStyle::Escaped => 0x1b,
+                        _ => return Err(Error::InvalidSymbol { symbol: escape, offset: position + 1 }),
+                    };
+
+                    position += 2;
+                    value
+                }
+            };
+
+            output.push(value);
+        }
+
+        if quote.is_some() {
+            return syntax_error(start - 1, "unterminated literal");
+        }
+
+        Ok(position)
+    }
+}
+
+impl Codec for Literal {
+    fn name(&self) -> String {
+        let name = match self.style {
+            LiteralStyle::C => "c",
+            LiteralStyle::Rust => "rust",
+            LiteralStyle::Python => "python",
+            LiteralStyle::Escaped => "escaped",
+        };
+
+        String::from(name)
+    }
+
+    fn encode(&self, input: &[u8]) -> Result<Vec<u8>> {
+        Ok(Literal::encode(self, input).into_bytes())
+    }
+
+    fn decode(&self, input: &[u8]) -> Result<Vec<u8>> {
+        Literal::decode(self, input)
+    }
+}
+
+// A list of numbers, optionally enclosed in braces after a declaration such as the
+// one printed by xxd -i. Anything after the closing brace is ignored.
+fn decode_c(input: &[u8], output: &mut Vec<u8>) -> Result<()> {
+    let (start, end) = match input.iter().position(|&b| b == b'{') {
+        Some(open) => match input[open..].iter().position(|&b| b == b'}') {
+            Some(close) => (open + 1, open + close),
+            None => return syntax_error(open, "unterminated array"),
+        },
+        None => (0, input.len()),
+    };
+
+    let elements: Vec<&[u8]> = input[start..end].split(|&b| b == b',').collect();
+    let mut offset = start;
+
+    for (i, element) in elements.iter().enumerate() {
+        let element_offset = offset;
+        offset += element.len() + 1;
+
+        let leading = element.iter().take_while(|&&b| is_whitespace(b)).count();
+        let trailing = element.iter().rev().take_while(|&&b| is_whitespace(b)).count();
+
+        // A trailing comma is fine, as is an empty array
+        if leading == element.len() {
+            if i + 1 == elements.len() {
+                break;
+            }
+            return syntax_error(element_offset + leading, "empty element");
+        }
+
+        let number = &element[leading..element.len() - trailing];
+        output.push(parse_c_number(number, element_offset + leading)?);
+    }
+
+    Ok(())
+}
+
+fn parse_c_number(number: &[u8], offset: usize) -> Result<u8> {
+    let (radix, skip) = if number.starts_with(b"0x") || number.starts_with(b"0X") {
+        (16, 2)
+    } else if number.len() > 1 && number[0] == b'0' {
+        (8, 1)
+    } else {
+        (10, 0)
+    };
+
+    if number.len() == skip {
+        return syntax_error(offset, "missing digits");
+    }
+
+    let mut value = 0;
+    for (i, &digit) in number.iter().enumerate().skip(skip) {
+        value = value * radix + digit_value(digit, radix, offset + i)?;
+        if value > 0xff {
+            return syntax_error(offset, "value out of range");
+        }
+    }
+
+    Ok(value as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use error::Error;
+    use super::*;
+
+    const INPUT: &'static [u8] = b"key \"\\\x00\xff\n";
+
+    #[test]
+    fn c_array() {
+        assert_eq!(Literal::c().encode(b"Hi\n"), "{ 0x48, 0x69, 0x0a }");
+        assert_eq!(Literal::c().encode(b""), "{}");
+        assert_eq!(Literal::c().decode("{ 0x48, 0x69, 0x0a }").unwrap(), b"Hi\n");
+    }
+
+    #[test]
+    fn c_array_decoding_formats() {
+        let xxd = "unsigned char key[] = {\n  0x4b, 0x45,\n  0x59\n};\nunsigned int key_len = 3;\n";
+        assert_eq!(Literal::c().decode(xxd).unwrap(), b"KEY");
+        assert_eq!(Literal::c().decode("75, 0x45, 0131,").unwrap(), b"KEY");
+        assert_eq!(Literal::c().decode("{}").unwrap(), b"");
+    }
+
+    #[test]
+    fn byte_strings() {
+        assert_eq!(Literal::rust().encode(INPUT), "b\"key \\\"\\\\\\x00\\xff\\n\"");
+        assert_eq!(Literal::python().encode(INPUT), "b\"key \\\"\\\\\\x00\\xff\\n\"");
+        assert_eq!(Literal::new().encode(INPUT), "key \"\\\\\\x00\\xff\\n");
+        assert_eq!(Literal::rust().with_escape_all(true).encode(b"Hi"), "b\"\\x48\\x69\"");
+    }
+
+    #[test]
+    fn byte_strings_round_trip() {
+        let input: Vec<u8> = (0..=255).collect();
+        for &style in [LiteralStyle::C, LiteralStyle::Rust, LiteralStyle::Python, LiteralStyle::Escaped].iter() {
+            for &escape_all in [false, true].iter() {
+                let literal = Literal::new().with_style(style).with_escape_all(escape_all);
+                assert_eq!(literal.decode(&literal.encode(&input)).unwrap(), input);
+            }
+        }
+    }
+
+    #[test]
+    fn python_literals() {
+        assert_eq!(Literal::python().decode("b'it\\'s' B\"\\101\\0\\a\"").unwrap(), b"it'sA\x00\x07");
+        assert_eq!(Literal::python().decode("b'\\x41\\\nB'\n").unwrap(), b"AB");
+    }
+
+    #[test]
+    fn rust_line_continuation() {
+        assert_eq!(Literal::rust().decode("b\"ab\\\n    cd\"").unwrap(), b"abcd");
+    }
+
+    #[test]
+    fn escaped_text() {
+        assert_eq!(Literal::new().decode("\\x41\\e[0m\\101\n").unwrap(), b"A\x1b[0mA");
+        assert_eq!(Literal::new().decode("a\nb"), Err(Error::InvalidSymbol { symbol: b'\n', offset: 1 }));
+    }
+
+    #[test]
+    fn decoding_errors() {
+        let error = |literal: &Literal, input: &str| literal.decode(input).err().unwrap();
+
+        assert_eq!(error(&Literal::c(), "{ 0x41, 0x100 }"), Error::Syntax { offset: 8, message: String::from("value out of range") });
+        assert_eq!(error(&Literal::c(), "0x41,,0x42"), Error::Syntax { offset: 5, message: String::from("empty element") });
+        assert_eq!(error(&Literal::c(), "0x4g"), Error::InvalidSymbol { symbol: b'g', offset: 3 });
+        assert_eq!(error(&Literal::c(), "{ 0x41"), Error::Syntax { offset: 0, message: String::from("unterminated array") });
+        assert_eq!(error(&Literal::rust(), "b\"abc"), Error::Syntax { offset: 1, message: String::from("unterminated literal") });
+        assert_eq!(error(&Literal::rust(), "b'abc'"), Error::Syntax { offset: 1, message: String::from("expected quote") });
+        assert_eq!(error(&Literal::rust(), "b\"a\" b\"c\""), Error::InvalidSymbol { symbol: b'b', offset: 5 });
+        assert_eq!(error(&Literal::rust(), "b\"\\a\""), Error::InvalidSymbol { symbol: b'a', offset: 3 });
+        assert_eq!(error(&Literal::python(), "'abc'"), Error::Syntax { offset: 0, message: String::from("expected byte string") });
+        assert_eq!(error(&Literal::python(), "b'\\x4'"), Error::InvalidSymbol { symbol: b'\'', offset: 5 });
+        assert_eq!(error(&Literal::python(), "b'\\777'"), Error::Syntax { offset: 2, message: String::from("value out of range") });
+    }
+}
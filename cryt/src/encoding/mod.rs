@@ -0,0 +1,269 @@
+use std::io::{self, Read};
+
+use error::{Error, Result};
+use pipeline::Codec;
+
+mod ascii85;
+mod auto;
+mod base32;
+mod base58;
+mod base64;
+mod hex;
+mod hexdump;
+mod literal;
+
+pub use self::ascii85::{ascii85_encode, ascii85_decode, z85_encode, z85_decode};
+pub use self::auto::{AutoDecoder, Chain};
+pub use self::base32::{base32_encode, base32_decode, Base32};
+pub use self::base58::{base58_encode, base58_decode, base58check_encode, base58check_decode};
+pub use self::base64::{base64_encode, base64_decode, Base64, Alphabet, Newline, Base64Encoder, Base64Decoder};
+pub use self::hex::{hex_encode, hex_decode, HexEncoder, HexDecoder};
+pub use self::hexdump::{hexdump_decode, Hexdump, HexdumpStyle};
+pub use self::literal::{Literal, LiteralStyle};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Padding {
+    Required,
+    Optional,
+    Omitted,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Encoding {
+    Hex,
+    Base32,
+    Base32Hex,
+    Base64,
+    Base64UrlSafe,
+    Base58,
+    Ascii85,
+    Z85,
+    Hexdump,
+    CArray,
+    RustBytes,
+    PythonBytes,
+    Escaped,
+}
+
+impl Encoding {
+    pub fn all() -> Vec<Encoding> {
+        vec![Encoding::Hex, Encoding::Base32, Encoding::Base32Hex, Encoding::Base64,
+             Encoding::Base64UrlSafe, Encoding::Base58, Encoding::Ascii85, Encoding::Z85,
+             Encoding::Hexdump, Encoding::CArray, Encoding::RustBytes, Encoding::PythonBytes,
+             Encoding::Escaped]
+    }
+
+    // Escaped text decodes to itself unless it holds escapes, so it is only tried on request
+    pub fn detectable() -> Vec<Encoding> {
+        Encoding::all().into_iter().filter(|&e| e != Encoding::Escaped).collect()
+    }
+
+    pub fn from_name(name: &str) -> Option<Encoding> {
+        Encoding::all().into_iter().find(|e| e.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Encoding::Hex => "hex",
+            Encoding::Base32 => "base32",
+            Encoding::Base32Hex => "base32hex",
+            Encoding::Base64 => "base64",
+            Encoding::Base64UrlSafe => "base64url",
+            Encoding::Base58 => "base58",
+            Encoding::Ascii85 => "ascii85",
+            Encoding::Z85 => "z85",
+            Encoding::Hexdump => "hexdump",
+            Encoding::CArray => "c",
+            Encoding::RustBytes => "rust",
+            Encoding::PythonBytes => "python",
+            Encoding::Escaped => "escaped",
+        }
+    }
+
+    pub fn encode(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        let encoded = match *self {
+            Encoding::Hex => hex_encode(bytes),
+            Encoding::Base32 => Base32::new().encode(bytes),
+            Encoding::Base32Hex => Base32::hex().encode(bytes),
+            Encoding::Base64 => Base64::new().encode(bytes),
+            Encoding::Base64UrlSafe => Base64::url_safe().encode(bytes),
+            Encoding::Base58 => base58_encode(bytes),
+            Encoding::Ascii85 => ascii85_encode(bytes),
+            Encoding::Z85 => z85_encode(bytes)?,
+            Encoding::Hexdump => Hexdump::new().encode(bytes),
+            Encoding::CArray => Literal::c().encode(bytes),
+            Encoding::RustBytes => Literal::rust().encode(bytes),
+            Encoding::PythonBytes => Literal::python().encode(bytes),
+            Encoding::Escaped => Literal::new().encode(bytes),
+        };
+
+        Ok(encoded.into_bytes())
+    }
+
+    // Decoding is as forgiving as each encoding allows, since input is often pasted
+    pub fn decode(&self, input: &[u8]) -> Result<Vec<u8>> {
+        match *self {
+            Encoding::Hex => hex_decode(input),
+            Encoding::Base32 => Base32::new().decode(input),
+            Encoding::Base32Hex => Base32::hex().decode(input),
+            Encoding::Base64 => Base64::new().with_padding(Padding::Optional).with_lenient(true).decode(input),
+            Encoding::Base64UrlSafe => Base64::url_safe().with_padding(Padding::Optional).decode(input),
+            Encoding::Base58 => base58_decode(input),
+            Encoding::Ascii85 => ascii85_decode(input),
+            Encoding::Z85 => z85_decode(input),
+            Encoding::Hexdump => hexdump_decode(input),
+            Encoding::CArray => Literal::c().decode(input),
+            Encoding::RustBytes => Literal::rust().decode(input),
+            Encoding::PythonBytes => Literal::python().decode(input),
+            Encoding::Escaped => Literal::new().decode(input),
+        }
+    }
+}
+
+impl Codec for Encoding {
+    fn name(&self) -> String {
+        String::from(Encoding::name(self))
+    }
+
+    fn encode(&self, input: &[u8]) -> Result<Vec<u8>> {
+        Encoding::encode(self, input)
+    }
+
+    fn decode(&self, input: &[u8]) -> Result<Vec<u8>> {
+        Encoding::decode(self, input)
+    }
+}
+
+// Amount of input processed at once by the streaming encoders and decoders
+const CHUNK_SIZE: usize = 8192;
+
+// A trailing line ending (as left by echo) is not part of the encoded data,
+// but one followed by more data is reported as an invalid symbol
+struct TrailingLineEnding {
+    pending: Option<(u8, usize)>,
+}
+
+impl TrailingLineEnding {
+    fn new() -> TrailingLineEnding {
+        TrailingLineEnding { pending: None }
+    }
+
+    fn skip(&mut self, symbol: u8, offset: usize) -> Result<bool> {
+        if symbol == b'\n' || symbol == b'\r' {
+            if self.pending.is_none() {
+                self.pending = Some((symbol, offset));
+            }
+
+            return Ok(true);
+        }
+
+        match self.pending {
+            Some((symbol, offset)) => Err(Error::InvalidSymbol { symbol, offset }),
+            None => Ok(false),
+        }
+    }
+}
+
+trait DecodeState {
+    fn update(&mut self, input: &[u8], output: &mut Vec<u8>) -> Result<()>;
+    fn finish(&mut self) -> Result<()>;
+}
+
+struct DecodingReader<R: Read, S: DecodeState> {
+    inner: R,
+    state: S,
+    input: Vec<u8>,
+    output: Vec<u8>,
+    position: usize,
+    finished: bool,
+}
+
+impl<R: Read, S: DecodeState> DecodingReader<R, S> {
+    fn new(inner: R, state: S) -> DecodingReader<R, S> {
+        DecodingReader {
+            inner,
+            state,
+            input: vec![0; CHUNK_SIZE],
+            output: Vec::with_capacity(CHUNK_SIZE),
+            position: 0,
+            finished: false,
+        }
+    }
+
+    fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read, S: DecodeState> Read for DecodingReader<R, S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.position < self.output.len() {
+                let count = buf.len().min(self.output.len() - self.position);
+                buf[..count].copy_from_slice(&self.output[self.position..self.position + count]);
+                self.position += count;
+                return Ok(count);
+            }
+
+            if self.finished {
+                return Ok(0);
+            }
+
+            self.output.clear();
+            self.position = 0;
+
+            let count = self.inner.read(&mut self.input)?;
+            if count == 0 {
+                self.finished = true;
+                self.state.finish()?;
+            } else {
+                self.state.update(&self.input[..count], &mut self.output)?;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Read};
+
+    use super::Encoding;
+
+    // Hands out a single byte per read to exercise chunk boundaries
+    pub struct OneByteReader<'a> {
+        input: &'a [u8],
+    }
+
+    impl<'a> OneByteReader<'a> {
+        pub fn new(input: &'a [u8]) -> OneByteReader<'a> {
+            OneByteReader { input: input }
+        }
+    }
+
+    impl<'a> Read for OneByteReader<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.input.is_empty() || buf.is_empty() {
+                return Ok(0);
+            }
+
+            buf[0] = self.input[0];
+            self.input = &self.input[1..];
+            Ok(1)
+        }
+    }
+
+    #[test]
+    fn encoding_round_trip() {
+        let input = b"\x00\x01encoded!\xff\xfe";
+        for encoding in Encoding::all() {
+            let encoded = encoding.encode(input).unwrap();
+            assert_eq!(encoding.decode(&encoded).unwrap(), &input[..], "{}", encoding.name());
+        }
+    }
+
+    #[test]
+    fn encoding_from_name() {
+        assert_eq!(Encoding::from_name("base64url"), Some(Encoding::Base64UrlSafe));
+        assert_eq!(Encoding::from_name("rot13"), None);
+    }
+}
@@ -0,0 +1,65 @@
+use std::error;
+use std::fmt;
+use std::io;
+use std::result;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    InvalidSymbol { symbol: u8, offset: usize },
+    InvalidLength { length: usize },
+    InvalidPadding { offset: usize },
+    InvalidChecksum,
+    Syntax { offset: usize, message: String },
+    // I/O errors are kept as their message so that errors can still be compared
+    Io(String),
+    InvalidModel(String),
+}
+
+pub type Result<T> = result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::InvalidSymbol { symbol, offset } => {
+                write!(f, "invalid symbol '{}' at offset {}", (symbol as char).escape_default(), offset)
+            }
+            Error::InvalidLength { length } => write!(f, "invalid input length {}", length),
+            Error::InvalidPadding { offset } => write!(f, "invalid padding at offset {}", offset),
+            Error::InvalidChecksum => write!(f, "invalid checksum"),
+            Error::Syntax { offset, ref message } => write!(f, "{} at offset {}", message, offset),
+            Error::Io(ref message) => write!(f, "{}", message),
+            Error::InvalidModel(ref message) => write!(f, "invalid model: {}", message),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+impl From<Error> for io::Error {
+    fn from(error: Error) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, error)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Error {
+        Error::Io(error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_symbol_is_escaped() {
+        let error = Error::InvalidSymbol { symbol: b'\n', offset: 8 };
+        assert_eq!(error.to_string(), "invalid symbol '\\n' at offset 8");
+    }
+
+    #[test]
+    fn invalid_length_message() {
+        let error = Error::InvalidLength { length: 3 };
+        assert_eq!(error.to_string(), "invalid input length 3");
+    }
+}
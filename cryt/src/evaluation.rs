@@ -0,0 +1,186 @@
+use xor::{Xor, SingleByteAttack, RepeatedAttack};
+
+// Xorshift pseudo-random generator: not suitable for keys, but small, fast and
+// reproducible from its seed
+pub struct XorShift {
+    state: u64,
+}
+
+impl XorShift {
+    pub fn new(seed: u64) -> XorShift {
+        // The state must never be zero
+        let state = if seed == 0 { 0x9e37_79b9_7f4a_7c15 } else { seed };
+        XorShift { state }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+        x
+    }
+
+    // A number in 0..bound
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    pub fn bytes(&mut self, count: usize) -> Vec<u8> {
+        (0..count).map(|_| (self.next_u64() >> 56) as u8).collect()
+    }
+}
+
+// Outcome of the trials with plaintexts of one length
+#[derive(Debug, Clone, PartialEq)]
+pub struct LengthResult {
+    pub length: usize,
+    pub trials: usize,
+    pub successes: usize,
+}
+
+impl LengthResult {
+    pub fn success_rate(&self) -> f32 {
+        if self.trials == 0 {
+            return 0.0;
+        }
+
+        self.successes as f32 / self.trials as f32
+    }
+}
+
+// Measures how often an attack recovers plaintexts taken from a corpus and
+// encrypted with random keys. Plaintexts and keys only depend on the seed, so
+// attacks evaluated with the same settings face the same ciphertexts.
+pub struct Evaluation {
+    corpus: Vec<u8>,
+    lengths: Vec<usize>,
+    trials: usize,
+    seed: u64,
+    min_keysize: usize,
+    max_keysize: usize,
+}
+
+impl Evaluation {
+    pub fn new(corpus: &[u8]) -> Evaluation {
+        Evaluation {
+            corpus: corpus.to_vec(),
+            lengths: vec![16, 32, 64, 128, 256],
+            trials: 20,
+            seed: 1,
+            min_keysize: 2,
+            max_keysize: 8,
+        }
+    }
+
+    pub fn with_lengths(mut self, lengths: &[usize]) -> Evaluation {
+        self.lengths = lengths.to_vec();
+        self
+    }
+
+    pub fn with_trials(mut self, trials: usize) -> Evaluation {
+        self.trials = trials;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Evaluation {
+        self.seed = seed;
+        self
+    }
+
+    // Lengths of the keys used against repeated xor attacks
+    pub fn with_keysizes(mut self, min: usize, max: usize) -> Evaluation {
+        self.min_keysize = min.max(1);
+        self.max_keysize = max.max(self.min_keysize);
+        self
+    }
+
+    // Runs the trials for every length, lengths longer than the corpus get none
+    fn evaluate<F: Fn(&[u8], &mut XorShift) -> bool>(&self, trial: F) -> Vec<LengthResult> {
+        let mut random = XorShift::new(self.seed);
+
+        self.lengths.iter().map(|&length| {
+            let mut result = LengthResult { length, trials: 0, successes: 0 };
+            if length == 0 || length > self.corpus.len() {
+                return result;
+            }
+
+            for _ in 0..self.trials {
+                let offset = random.below(self.corpus.len() - length + 1);
+                result.trials += 1;
+                if trial(&self.corpus[offset..offset + length], &mut random) {
+                    result.successes += 1;
+                }
+            }
+
+            result
+        }).collect()
+    }
+
+    pub fn single_byte(&self, attack: &SingleByteAttack) -> Vec<LengthResult> {
+        self.evaluate(|plaintext, random| {
+            let key = random.bytes(1);
+            attack.result(&Xor::new(&key).encrypt(plaintext)).plaintext == plaintext
+        })
+    }
+
+    pub fn repeated(&self, attack: &RepeatedAttack) -> Vec<LengthResult> {
+        self.evaluate(|plaintext, random| {
+            let keysize = self.min_keysize + random.below(self.max_keysize - self.min_keysize + 1);
+            let key = random.bytes(keysize);
+            attack.result(&Xor::new(&key).encrypt(plaintext)).plaintext == plaintext
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use criteria::{english_frequency, make_common_byte};
+    use xor::KeysizeAttack;
+
+    use super::*;
+
+    const CORPUS: &'static [u8] = b"\
+Call me Ishmael. Some years ago, never mind how long precisely, having little or no
+money in my purse, and nothing particular to interest me on shore, I thought I would
+sail about a little and see the watery part of the world. It is a way I have of
+driving off the spleen and regulating the circulation.";
+
+    #[test]
+    fn xorshift_is_reproducible() {
+        let first: Vec<u64> = { let mut r = XorShift::new(42); (0..4).map(|_| r.next_u64()).collect() };
+        let second: Vec<u64> = { let mut r = XorShift::new(42); (0..4).map(|_| r.next_u64()).collect() };
+
+        assert_eq!(first, second);
+        assert!(XorShift::new(0).next_u64() != 0);
+        assert!(XorShift::new(7).below(10) < 10);
+    }
+
+    #[test]
+    fn single_byte_success_rates() {
+        let evaluation = Evaluation::new(CORPUS).with_lengths(&[64, 1000]).with_trials(10);
+
+        let english = evaluation.single_byte(&SingleByteAttack::new().with_criterion(Box::new(english_frequency)));
+        let zeros = evaluation.single_byte(&SingleByteAttack::new().with_criterion(make_common_byte(0)));
+
+        assert_eq!(english, [LengthResult { length: 64, trials: 10, successes: 10 },
+                             LengthResult { length: 1000, trials: 0, successes: 0 }]);
+        assert_eq!(english[0].success_rate(), 1.0);
+        assert_eq!(zeros[0].successes, 0);
+        assert_eq!(zeros[1].success_rate(), 0.0);
+    }
+
+    #[test]
+    fn repeated_success_rate() {
+        let attack = RepeatedAttack::new()
+            .with_single_byte_attack(SingleByteAttack::new().with_criterion(Box::new(english_frequency)))
+            .with_keysize_attack(KeysizeAttack::new().with_max_length(6))
+            .with_result_criterion(Box::new(english_frequency))
+            .with_keysizes_count(3);
+
+        let results = Evaluation::new(CORPUS).with_lengths(&[200]).with_trials(5).with_keysizes(2, 3).repeated(&attack);
+        assert_eq!(results[0].trials, 5);
+        assert!(results[0].successes >= 4);
+    }
+}
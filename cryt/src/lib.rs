@@ -1,4 +1,13 @@
+extern crate regex;
+
 mod bytes;
+mod parallel;
+mod sha256;
 pub mod criteria;
 pub mod encoding;
+pub mod error;
+pub mod evaluation;
+pub mod pipeline;
 pub mod xor;
+
+pub use error::{Error, Result};
@@ -0,0 +1,38 @@
+use std::thread;
+
+// Applies the function to the items on up to the given number of threads, each
+// taking a contiguous share of them, and returns the results in the order of the
+// items whatever the number of threads
+pub fn parallel_map<T, R, F>(items: &[T], threads: usize, function: F) -> Vec<R>
+    where T: Sync, R: Send, F: Fn(&T) -> R + Sync {
+    if threads <= 1 || items.len() <= 1 {
+        return items.iter().map(function).collect();
+    }
+
+    let share = (items.len() + threads - 1) / threads;
+    let function = &function;
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = items.chunks(share)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(function).collect::<Vec<R>>()))
+            .collect();
+
+        handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn results_keep_the_order_of_the_items() {
+        let items: Vec<u32> = (0..100).collect();
+        let expected: Vec<u32> = items.iter().map(|i| i * i).collect();
+
+        for &threads in [0, 1, 3, 8, 100, 200].iter() {
+            assert_eq!(parallel_map(&items, threads, |i| i * i), expected);
+        }
+        assert!(parallel_map(&[] as &[u32], 4, |i| *i).is_empty());
+    }
+}
@@ -0,0 +1,273 @@
+use encoding::Encoding;
+use error::{Error, Result};
+use xor::Xor;
+
+pub trait Codec {
+    fn name(&self) -> String;
+    fn encode(&self, input: &[u8]) -> Result<Vec<u8>>;
+    fn decode(&self, input: &[u8]) -> Result<Vec<u8>>;
+}
+
+pub trait Transform {
+    fn name(&self) -> String;
+    fn apply(&self, input: &[u8]) -> Result<Vec<u8>>;
+}
+
+pub struct Encode<C: Codec>(pub C);
+
+impl<C: Codec> Transform for Encode<C> {
+    fn name(&self) -> String {
+        format!("{} encode", self.0.name())
+    }
+
+    fn apply(&self, input: &[u8]) -> Result<Vec<u8>> {
+        self.0.encode(input)
+    }
+}
+
+pub struct Decode<C: Codec>(pub C);
+
+impl<C: Codec> Transform for Decode<C> {
+    fn name(&self) -> String {
+        format!("{} decode", self.0.name())
+    }
+
+    fn apply(&self, input: &[u8]) -> Result<Vec<u8>> {
+        self.0.decode(input)
+    }
+}
+
+pub struct Pipeline {
+    transforms: Vec<Box<Transform>>,
+}
+
+impl Pipeline {
+    pub fn new() -> Pipeline {
+        Pipeline { transforms: Vec::new() }
+    }
+
+    pub fn then(mut self, transform: Box<Transform>) -> Pipeline {
+        self.transforms.push(transform);
+        self
+    }
+
+    // Stages are separated by '|', e.g. "b64d | xor -k ICE | hex". An encoding name
+    // encodes and the same name followed by 'd' decodes. Words may be quoted.
+    pub fn parse(spec: &str) -> Result<Pipeline> {
+        let mut pipeline = Pipeline::new();
+        for stage in tokenize(spec)? {
+            pipeline = pipeline.then(parse_stage(&stage)?);
+        }
+
+        Ok(pipeline)
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.transforms.iter().map(|t| t.name()).collect()
+    }
+}
+
+impl Transform for Pipeline {
+    fn name(&self) -> String {
+        self.names().join(" | ")
+    }
+
+    fn apply(&self, input: &[u8]) -> Result<Vec<u8>> {
+        let mut result = input.to_vec();
+        for transform in self.transforms.iter() {
+            result = transform.apply(&result)?;
+        }
+
+        Ok(result)
+    }
+}
+
+struct Word {
+    offset: usize,
+    text: String,
+}
+
+struct Stage {
+    offset: usize,
+    words: Vec<Word>,
+}
+
+fn syntax_error<T>(offset: usize, message: &str) -> Result<T> {
+    Err(Error::Syntax { offset, message: String::from(message) })
+}
+
+fn tokenize(spec: &str) -> Result<Vec<Stage>> {
+    let mut stages = vec![Stage { offset: 0, words: Vec::new() }];
+    let mut word: Option<Word> = None;
+    let mut quote: Option<(usize, char)> = None;
+
+    for (offset, c) in spec.char_indices() {
+        if let Some((_, q)) = quote {
+            if c == q {
+                quote = None;
+            } else {
+                word.get_or_insert(Word { offset, text: String::new() }).text.push(c);
+            }
+            continue;
+        }
+
+        match c {
+            '\'' | '"' => {
+                quote = Some((offset, c));
+                word.get_or_insert(Word { offset, text: String::new() });
+            }
+            '|' | ' ' | '\t' | '\n' | '\r' => {
+                if let Some(w) = word.take() {
+                    stages.last_mut().unwrap().words.push(w);
+                }
+
+                if c == '|' {
+                    if stages.last().unwrap().words.is_empty() {
+                        return syntax_error(offset, "empty stage");
+                    }
+                    stages.push(Stage { offset: offset + 1, words: Vec::new() });
+                }
+            }
+            c => word.get_or_insert(Word { offset, text: String::new() }).text.push(c),
+        }
+    }
+
+    if let Some((offset, _)) = quote {
+        return syntax_error(offset, "unterminated quote");
+    }
+
+    if let Some(w) = word.take() {
+        stages.last_mut().unwrap().words.push(w);
+    }
+
+    if stages.last().unwrap().words.is_empty() {
+        return syntax_error(spec.len(), "empty stage");
+    }
+
+    Ok(stages)
+}
+
+fn encoding_by_name(name: &str) -> Option<Encoding> {
+    let name = match name {
+        "b32" => "base32",
+        "b32hex" => "base32hex",
+        "b64" => "base64",
+        "b64url" => "base64url",
+        "b58" => "base58",
+        "a85" => "ascii85",
+        "py" => "python",
+        "esc" => "escaped",
+        name => name,
+    };
+
+    Encoding::from_name(name)
+}
+
+fn parse_stage(stage: &Stage) -> Result<Box<Transform>> {
+    let command = &stage.words[0];
+    if command.text == "xor" {
+        return parse_xor(stage);
+    }
+
+    if let Some(word) = stage.words.get(1) {
+        return syntax_error(word.offset, "unexpected argument");
+    }
+
+    if let Some(encoding) = encoding_by_name(&command.text) {
+        return Ok(Box::new(Encode(encoding)));
+    }
+
+    if command.text.ends_with('d') {
+        if let Some(encoding) = encoding_by_name(&command.text[..command.text.len() - 1]) {
+            return Ok(Box::new(Decode(encoding)));
+        }
+    }
+
+    syntax_error(command.offset, "unknown transform")
+}
+
+// xor -k KEY, or xor -x HEXKEY
+fn parse_xor(stage: &Stage) -> Result<Box<Transform>> {
+    let mut key = None;
+    let mut words = stage.words.iter().skip(1);
+
+    while let Some(option) = words.next() {
+        let value = match words.next() {
+            Some(value) => value,
+            None => return syntax_error(option.offset, "missing option value"),
+        };
+
+        key = match option.text.as_str() {
+            "-k" | "--key" => Some(value.text.as_bytes().to_vec()),
+            "-x" | "--hex-key" => Some(Encoding::Hex.decode(value.text.as_bytes())
+                                       .or_else(|_| syntax_error(value.offset, "invalid hex key"))?),
+            _ => return syntax_error(option.offset, "unknown option"),
+        };
+    }
+
+    match key {
+        Some(ref key) if !key.is_empty() => Ok(Box::new(Encode(Xor::new(key)))),
+        _ => syntax_error(stage.offset, "missing xor key"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use encoding::{Base64, Encoding};
+    use error::Error;
+    use xor::Xor;
+    use super::*;
+
+    const PLAIN_TEXT: &'static str = "Burning 'em, if you ain't quick and nimble\nI go crazy when I hear a cymbal";
+    const ENCRYPTED_HEX: &'static str = "0b3637272a2b2e63622c2e69692a23693a2a3c6324202d623d63343c2a26226324272765272a282b2f20430a652e2c652a3124333a653e2b2027630c692b20283165286326302e27282f";
+
+    #[test]
+    fn pipeline_of_transforms() {
+        let pipeline = Pipeline::new()
+            .then(Box::new(Encode(Xor::new(b"ICE"))))
+            .then(Box::new(Encode(Encoding::Hex)));
+
+        assert_eq!(pipeline.apply(PLAIN_TEXT.as_bytes()).unwrap(), ENCRYPTED_HEX.as_bytes());
+        assert_eq!(pipeline.name(), "xor encode | hex encode");
+    }
+
+    #[test]
+    fn parsed_pipeline() {
+        let input = Base64::new().encode(PLAIN_TEXT.as_bytes());
+        let pipeline = Pipeline::parse("b64d | xor -k ICE | hex").unwrap();
+
+        assert_eq!(pipeline.apply(input.as_bytes()).unwrap(), ENCRYPTED_HEX.as_bytes());
+    }
+
+    #[test]
+    fn parsed_pipeline_round_trip() {
+        let pipeline = Pipeline::parse("hexd|xor --hex-key 494345|base64|base64d").unwrap();
+
+        assert_eq!(pipeline.apply(ENCRYPTED_HEX.as_bytes()).unwrap(), PLAIN_TEXT.as_bytes());
+    }
+
+    #[test]
+    fn quoted_xor_key() {
+        let pipeline = Pipeline::parse("xor -k 'a | b' | hex").unwrap();
+        assert_eq!(pipeline.apply(b"\x00\x00\x00\x00\x00").unwrap(), b"61207c2062");
+    }
+
+    #[test]
+    fn decoding_errors_are_returned() {
+        let pipeline = Pipeline::parse("hexd").unwrap();
+        assert_eq!(pipeline.apply(b"0g"), Err(Error::InvalidSymbol { symbol: b'g', offset: 1 }));
+    }
+
+    #[test]
+    fn syntax_errors() {
+        let error = |spec| Pipeline::parse(spec).err().unwrap();
+
+        assert_eq!(error("hex | rot13"), Error::Syntax { offset: 6, message: String::from("unknown transform") });
+        assert_eq!(error("hex || b64"), Error::Syntax { offset: 5, message: String::from("empty stage") });
+        assert_eq!(error("hex |"), Error::Syntax { offset: 5, message: String::from("empty stage") });
+        assert_eq!(error("xor -k"), Error::Syntax { offset: 4, message: String::from("missing option value") });
+        assert_eq!(error("xor"), Error::Syntax { offset: 0, message: String::from("missing xor key") });
+        assert_eq!(error("xor -k 'ICE"), Error::Syntax { offset: 7, message: String::from("unterminated quote") });
+        assert_eq!(error("hex b64"), Error::Syntax { offset: 4, message: String::from("unexpected argument") });
+    }
+}
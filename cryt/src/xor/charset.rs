@@ -0,0 +1,69 @@
+// The bytes a key may be made of. Keys are often typed passphrases, so trying
+// only printable or alphanumeric bytes avoids decryptions that merely score well
+// and makes attacks faster.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Charset {
+    All,
+    Printable,
+    Alphanumeric,
+    HexDigits,
+    Custom(Vec<u8>),
+}
+
+impl Charset {
+    // The charsets that have a name
+    pub fn named() -> Vec<Charset> {
+        vec![Charset::All, Charset::Printable, Charset::Alphanumeric, Charset::HexDigits]
+    }
+
+    pub fn from_name(name: &str) -> Option<Charset> {
+        Charset::named().into_iter().find(|c| c.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Charset::All => "all",
+            Charset::Printable => "printable",
+            Charset::Alphanumeric => "alphanumeric",
+            Charset::HexDigits => "hex",
+            Charset::Custom(_) => "custom",
+        }
+    }
+
+    pub fn contains(&self, byte: u8) -> bool {
+        match *self {
+            Charset::All => true,
+            Charset::Printable => byte >= 0x20 && byte <= 0x7e,
+            Charset::Alphanumeric => byte.is_ascii_alphanumeric(),
+            Charset::HexDigits => byte.is_ascii_hexdigit(),
+            Charset::Custom(ref bytes) => bytes.contains(&byte),
+        }
+    }
+
+    // The bytes of the charset, from the lowest
+    pub fn keys(&self) -> Vec<u8> {
+        (0..=255u8).filter(|&b| self.contains(b)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn charset_keys() {
+        assert_eq!(Charset::All.keys().len(), 256);
+        assert_eq!(Charset::Printable.keys().len(), 95);
+        assert_eq!(Charset::Alphanumeric.keys().len(), 62);
+        assert_eq!(Charset::HexDigits.keys(), b"0123456789ABCDEFabcdef".to_vec());
+        assert_eq!(Charset::Custom(b"zaz\x00".to_vec()).keys(), b"\x00az".to_vec());
+        assert!(Charset::Custom(Vec::new()).keys().is_empty());
+    }
+
+    #[test]
+    fn charset_from_name() {
+        assert_eq!(Charset::from_name("hex"), Some(Charset::HexDigits));
+        assert_eq!(Charset::from_name("custom"), None);
+        assert_eq!(Charset::Custom(vec![1]).name(), "custom");
+    }
+}
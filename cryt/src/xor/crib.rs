@@ -0,0 +1,188 @@
+use criteria::{Criterion, text_bytes};
+use super::compare_scores;
+
+// A position of the crib found by dragging it: the message and offset it is put at,
+// the mean score of the other messages there and the fragments of all the messages
+#[derive(Debug, Clone, PartialEq)]
+pub struct CribResult {
+    pub message: usize,
+    pub offset: usize,
+    pub score: f32,
+    pub fragments: Vec<Vec<u8>>,
+}
+
+// Crib dragging against messages encrypted with the same keystream. Assuming that
+// a message holds a guessed word (the crib) at some offset gives the keystream
+// there, and so the same span of every other message: positions where those spans
+// look like plaintext are likely right, and can be locked in to recover the
+// keystream little by little.
+pub struct CribDrag {
+    ciphertexts: Vec<Vec<u8>>,
+    criterion: Box<Criterion>,
+    keystream: Vec<Option<u8>>,
+}
+
+impl CribDrag {
+    pub fn new<T: AsRef<[u8]>>(ciphertexts: &[T]) -> CribDrag {
+        let ciphertexts: Vec<Vec<u8>> = ciphertexts.iter().map(|c| c.as_ref().to_vec()).collect();
+        let length = ciphertexts.iter().map(|c| c.len()).max().unwrap_or(0);
+
+        CribDrag {
+            ciphertexts,
+            criterion: Box::new(text_bytes),
+            keystream: vec![None; length],
+        }
+    }
+
+    pub fn with_criterion(mut self, criterion: Box<Criterion>) -> CribDrag {
+        self.criterion = criterion;
+        self
+    }
+
+    pub fn messages(&self) -> usize {
+        self.ciphertexts.len()
+    }
+
+    // The keystream bytes recovered so far
+    pub fn keystream(&self) -> &[Option<u8>] {
+        &self.keystream
+    }
+
+    // What the other messages hold when the given one holds the crib at the offset,
+    // or None when the crib does not fit in the message or no other message reaches
+    // the offset
+    pub fn try_crib(&self, crib: &[u8], message: usize, offset: usize) -> Option<(f32, Vec<Vec<u8>>)> {
+        let ciphertext = &self.ciphertexts[message];
+        if crib.is_empty() || offset + crib.len() > ciphertext.len() {
+            return None;
+        }
+
+        let keystream: Vec<u8> = ciphertext[offset..].iter().zip(crib.iter()).map(|(c, p)| c ^ p).collect();
+        let fragments: Vec<Vec<u8>> = self.ciphertexts.iter()
+            .map(|c| c.iter().skip(offset).zip(keystream.iter()).map(|(c, k)| c ^ k).collect())
+            .collect();
+
+        let scores: Vec<f32> = fragments.iter()
+            .enumerate()
+            .filter(|&(i, fragment)| i != message && !fragment.is_empty())
+            .map(|(_, fragment)| self.criterion.score(fragment))
+            .collect();
+
+        if scores.is_empty() {
+            return None;
+        }
+
+        let score = scores.iter().sum::<f32>() / scores.len() as f32;
+        Some((score, fragments))
+    }
+
+    // Slides the crib across every message, or only the given one. Results rank from
+    // the best score, ties the lower message and then the lower offset first.
+    pub fn drag(&self, crib: &[u8], message: Option<usize>) -> Vec<CribResult> {
+        let messages: Vec<usize> = match message {
+            Some(message) => vec![message],
+            None => (0..self.ciphertexts.len()).collect(),
+        };
+
+        let mut results = Vec::new();
+        for message in messages {
+            for offset in 0..self.ciphertexts[message].len() {
+                if let Some((score, fragments)) = self.try_crib(crib, message, offset) {
+                    results.push(CribResult { message, offset, score, fragments });
+                }
+            }
+        }
+
+        results.sort_by(|r1, r2| {
+            compare_scores(r1.score, r2.score).then(r1.message.cmp(&r2.message)).then(r1.offset.cmp(&r2.offset))
+        });
+        results
+    }
+
+    // Takes the plaintext of a message at the offset as known, which sets the
+    // keystream there. The plaintext is cut to the length of the message.
+    pub fn lock(&mut self, message: usize, offset: usize, plaintext: &[u8]) {
+        let ciphertext = &self.ciphertexts[message];
+        for (i, &p) in plaintext.iter().enumerate().take(ciphertext.len().saturating_sub(offset)) {
+            self.keystream[offset + i] = Some(ciphertext[offset + i] ^ p);
+        }
+    }
+
+    // Forgets the keystream of the given span
+    pub fn unlock(&mut self, offset: usize, length: usize) {
+        for k in self.keystream.iter_mut().skip(offset).take(length) {
+            *k = None;
+        }
+    }
+
+    // The plaintext of a message, None where the keystream is unknown
+    pub fn plaintext(&self, message: usize) -> Vec<Option<u8>> {
+        self.ciphertexts[message].iter()
+            .zip(self.keystream.iter())
+            .map(|(&c, &k)| k.map(|k| c ^ k))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xor::Xor;
+    use super::*;
+
+    const KEY: &'static [u8] = b"\x8a\x1f\x5c\x03\xe7\x41\x99\x2d\x60\xb3\x0c\x77\xd5\x18\x4e\xa2\x3b\xc9\x71\x06\xfe\x52";
+
+    fn ciphertexts() -> Vec<Vec<u8>> {
+        vec![
+            Xor::new(KEY).encrypt(b"meet me at the docks"),
+            Xor::new(KEY).encrypt(b"bring the money then"),
+            Xor::new(KEY).encrypt(b"ok"),
+        ]
+    }
+
+    #[test]
+    fn crib_reveals_other_messages() {
+        let drag = CribDrag::new(&ciphertexts());
+
+        let (score, fragments) = drag.try_crib(b" the ", 0, 10).unwrap();
+        assert_eq!(fragments, [b" the ".to_vec(), b"money".to_vec(), Vec::new()]);
+        assert_eq!(score, 1.0);
+
+        assert!(drag.try_crib(b" the ", 0, 16).is_none());
+        assert!(drag.try_crib(b"the", 1, 5).is_some());
+        assert!(drag.try_crib(b"", 1, 5).is_none());
+    }
+
+    #[test]
+    fn drag_ranks_positions() {
+        let drag = CribDrag::new(&ciphertexts()).with_criterion(Box::new(|b: &[u8]| {
+            b.iter().filter(|&&c| c.is_ascii_lowercase() || c == b' ').count() as f32 / b.len() as f32
+        }));
+
+        let results = drag.drag(b"money", Some(1));
+        assert_eq!(results.len(), 16);
+        assert_eq!((results[0].message, results[0].offset, results[0].score), (1, 10, 1.0));
+        assert_eq!(results[0].fragments[0], b" the ".to_vec());
+
+        let results = drag.drag(b" the ", None);
+        let best: Vec<(usize, usize)> = results.iter().take(4).map(|r| (r.message, r.offset)).collect();
+
+        // The crib in one message at an offset also shows the other message there
+        assert_eq!(best, [(0, 5), (0, 10), (1, 5), (1, 10)]);
+        assert_eq!(drag.drag(b"ok", None).len(), 19 + 19 + 1);
+    }
+
+    #[test]
+    fn lock_and_unlock() {
+        let mut drag = CribDrag::new(&ciphertexts());
+        drag.lock(1, 0, b"bring");
+        drag.lock(2, 1, b"kxyz");
+
+        assert_eq!(drag.plaintext(0)[..6], [Some(b'm'), Some(b'e'), Some(b'e'), Some(b't'), Some(b' '), None]);
+        assert_eq!(drag.plaintext(2), [Some(b'o'), Some(b'k')]);
+        assert_eq!(drag.keystream()[2], Some(0x5c));
+
+        drag.unlock(1, 1);
+        assert_eq!(drag.plaintext(2), [Some(b'o'), None]);
+        assert_eq!(drag.messages(), 3);
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+
+use criteria::index_of_coincidence;
+
+// Length of the repeated sequences looked for by the Kasiski examination
+const KASISKI_LENGTH: usize = 3;
+
+// Kasiski examination: a sequence that repeats in the plaintext at a distance that
+// is a multiple of the key length also repeats in the ciphertext. The score is the
+// share of the distances between repeated sequences that the size divides, less the
+// 1/size expected by chance, so that smaller sizes are not favoured.
+pub fn kasiski_criterion(input: &[u8], size: u32) -> f32 {
+    let mut last_positions = HashMap::new();
+    let mut distances = Vec::new();
+
+    for (position, sequence) in input.windows(KASISKI_LENGTH).enumerate() {
+        if let Some(last) = last_positions.insert(sequence, position) {
+            distances.push(position - last);
+        }
+    }
+
+    if distances.is_empty() || size == 0 {
+        return 0.0;
+    }
+
+    let divided = distances.iter().filter(|&&d| d % size as usize == 0).count();
+    divided as f32 / distances.len() as f32 - 1.0 / size as f32
+}
+
+// Average index of coincidence of the columns of bytes xored with the same key
+// byte, which keep the high index of coincidence of the plaintext when the size is
+// right. Multiples of the key length score as high.
+pub fn index_of_coincidence_criterion(input: &[u8], size: u32) -> f32 {
+    let size = size as usize;
+    if size == 0 {
+        return 0.0;
+    }
+
+    let columns: Vec<Vec<u8>> = (0..size)
+        .map(|column| input.iter().skip(column).step_by(size).cloned().collect())
+        .filter(|column: &Vec<u8>| column.len() >= 2)
+        .collect();
+
+    if columns.is_empty() {
+        return 0.0;
+    }
+
+    columns.iter().map(|c| index_of_coincidence(c)).sum::<f32>() / columns.len() as f32
+}
+
+// Proportion of the bytes that are equal to the byte size positions further, which
+// is that of the plaintext when the input is shifted by a multiple of the key length.
+// Multiples of the key length score as high.
+pub fn autocorrelation_criterion(input: &[u8], size: u32) -> f32 {
+    let size = size as usize;
+    if size == 0 || input.len() <= size {
+        return 0.0;
+    }
+
+    let equal = input.iter().zip(input[size..].iter()).filter(|&(a, b)| a == b).count();
+    equal as f32 / (input.len() - size) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use xor::{KeysizeAttack, Xor};
+    use super::*;
+
+    const PLAINTEXT: &'static [u8] = b"\
+It is a truth universally acknowledged, that a single man in possession of a good
+fortune, must be in want of a wife. However little known the feelings or views of
+such a man may be on his first entering a neighbourhood, this truth is so well fixed
+in the minds of the surrounding families, that he is considered the rightful property
+of some one or other of their daughters.";
+
+    fn best_keysize(criterion: fn(&[u8], u32) -> f32, key: &[u8]) -> u32 {
+        KeysizeAttack::new()
+            .with_max_length(20)
+            .with_criterion(Box::new(criterion))
+            .result(&Xor::new(key).encrypt(PLAINTEXT))[0].keysize
+    }
+
+    #[test]
+    fn kasiski() {
+        assert_eq!(best_keysize(kasiski_criterion, b"lemon"), 5);
+        assert_eq!(best_keysize(kasiski_criterion, b"Austen"), 6);
+        assert_eq!(kasiski_criterion(b"abcdefgh", 2), 0.0);
+        assert_eq!(kasiski_criterion(b"abcabcabc", 3), 1.0 - 1.0 / 3.0);
+    }
+
+    #[test]
+    fn index_of_coincidence() {
+        // A multiple of the key length is as good a key length
+        assert_eq!(best_keysize(index_of_coincidence_criterion, b"lemon") % 5, 0);
+        assert_eq!(best_keysize(index_of_coincidence_criterion, b"Austen") % 6, 0);
+        assert_eq!(index_of_coincidence_criterion(b"abab", 2), 1.0);
+        assert_eq!(index_of_coincidence_criterion(b"ab", 4), 0.0);
+    }
+
+    #[test]
+    fn autocorrelation() {
+        assert_eq!(best_keysize(autocorrelation_criterion, b"lemon") % 5, 0);
+        assert_eq!(best_keysize(autocorrelation_criterion, b"Austen") % 6, 0);
+        assert_eq!(autocorrelation_criterion(b"abcabd", 3), 2.0 / 3.0);
+        assert_eq!(autocorrelation_criterion(b"abc", 3), 0.0);
+    }
+}
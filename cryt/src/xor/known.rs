@@ -0,0 +1,129 @@
+use super::Xor;
+
+// Outcome of a known plaintext attack: the key, aligned with the start of the
+// input, and the decrypted input
+#[derive(Debug, Clone, PartialEq)]
+pub struct KnownPlaintextResult {
+    pub key: Vec<u8>,
+    pub plaintext: Vec<u8>,
+}
+
+// Recovers a repeating xor key from a known part of the plaintext, such as a file
+// header or a protocol prefix. Xoring the crib with the ciphertext at its offset
+// gives a fragment of the keystream, whose smallest period is taken as the key
+// length unless the period is given.
+pub struct KnownPlaintextAttack {
+    crib: Vec<u8>,
+    offset: usize,
+    period: Option<usize>,
+}
+
+impl KnownPlaintextAttack {
+    pub fn new(crib: &[u8]) -> KnownPlaintextAttack {
+        KnownPlaintextAttack {
+            crib: crib.to_vec(),
+            offset: 0,
+            period: None,
+        }
+    }
+
+    // Position of the crib in the plaintext
+    pub fn with_offset(mut self, offset: usize) -> KnownPlaintextAttack {
+        self.offset = offset;
+        self
+    }
+
+    // Length of the key when it is known, e.g. from a keysize attack
+    pub fn with_period(mut self, period: usize) -> KnownPlaintextAttack {
+        self.period = Some(period);
+        self
+    }
+
+    // The keystream under the crib, cut to the end of the input
+    pub fn keystream(&self, input: &[u8]) -> Vec<u8> {
+        input.iter().skip(self.offset).zip(self.crib.iter()).map(|(c, p)| c ^ p).collect()
+    }
+
+    // The smallest period of the keystream under the crib, which is its whole
+    // length when it does not repeat
+    pub fn period(&self, input: &[u8]) -> usize {
+        let keystream = self.keystream(input);
+        (1..keystream.len())
+            .find(|&p| is_period(&keystream, p))
+            .unwrap_or(keystream.len())
+    }
+
+    // None when the crib is not within the input, or does not cover a whole period,
+    // or the given period does not match the keystream
+    pub fn result(&self, input: &[u8]) -> Option<KnownPlaintextResult> {
+        let keystream = self.keystream(input);
+        let period = self.period.unwrap_or_else(|| self.period(input));
+        if keystream.is_empty() || period == 0 || period > keystream.len() || !is_period(&keystream, period) {
+            return None;
+        }
+
+        let mut key = vec![0; period];
+        for (i, &k) in keystream.iter().take(period).enumerate() {
+            key[(self.offset + i) % period] = k;
+        }
+
+        let plaintext = Xor::new(&key).decrypt(input);
+        Some(KnownPlaintextResult { key, plaintext })
+    }
+}
+
+fn is_period(keystream: &[u8], period: usize) -> bool {
+    keystream.iter().zip(keystream.iter().skip(period)).all(|(a, b)| a == b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAINTEXT: &'static [u8] = b"%PDF-1.7\n%\xe2\xe3\xcf\xd3\n1 0 obj\n<< /Type /Catalog >>\nendobj\n";
+
+    fn result(key: &[u8]) -> Option<KnownPlaintextResult> {
+        Some(KnownPlaintextResult { key: key.to_vec(), plaintext: PLAINTEXT.to_vec() })
+    }
+
+    #[test]
+    fn recovers_key_from_header() {
+        let input = Xor::new(b"k3y!").encrypt(PLAINTEXT);
+        let attack = KnownPlaintextAttack::new(b"%PDF-1.7\n");
+
+        assert_eq!(attack.period(&input), 4);
+        assert_eq!(attack.result(&input), result(b"k3y!"));
+    }
+
+    #[test]
+    fn crib_at_offset() {
+        let input = Xor::new(b"secret").encrypt(PLAINTEXT);
+        let attack = KnownPlaintextAttack::new(b"1 0 obj\n<< /Type").with_offset(15);
+
+        assert_eq!(attack.keystream(&input)[..4], *b"rets");
+        assert_eq!(attack.result(&input), result(b"secret"));
+    }
+
+    #[test]
+    fn given_period() {
+        let input = Xor::new(b"abcdef").encrypt(PLAINTEXT);
+
+        // A crib shorter than the key does not repeat, and is taken as the whole key
+        let crib = KnownPlaintextAttack::new(b"%PDF-");
+        assert_eq!(crib.period(&input), 5);
+        assert_eq!(crib.result(&input).unwrap().key, b"abcde".to_vec());
+        assert_eq!(crib.with_period(6).result(&input), None);
+
+        let attack = KnownPlaintextAttack::new(b"%PDF-1.7\n").with_period(6);
+        assert_eq!(attack.result(&input).unwrap().plaintext, PLAINTEXT.to_vec());
+        assert_eq!(KnownPlaintextAttack::new(b"%PDF-1.7\n").with_period(4).result(&input), None);
+    }
+
+    #[test]
+    fn crib_outside_input() {
+        let attack = KnownPlaintextAttack::new(b"abc").with_offset(10);
+        assert_eq!(attack.result(b"short"), None);
+        assert_eq!(attack.period(b"short"), 0);
+        assert_eq!(KnownPlaintextAttack::new(b"abcdef").keystream(b"xyz").len(), 3);
+    }
+}
@@ -0,0 +1,99 @@
+use criteria::{english_frequency, text_bytes};
+use super::{SingleByteAttack, Xor};
+
+// Outcome of a many-time pad attack, with the margin of each keystream byte as
+// solved by the single byte attack
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManyTimePadResult {
+    pub keystream: Vec<u8>,
+    pub plaintexts: Vec<Vec<u8>>,
+    pub margins: Vec<f32>,
+}
+
+// Attack on messages encrypted with the same keystream, such as a reused one time
+// pad or CTR with a fixed nonce. The nth bytes of all the messages are xored with
+// the same keystream byte, so each column is solved as single byte xor. Messages
+// may have different lengths, the columns past the end of the shorter ones just
+// hold fewer bytes.
+pub struct ManyTimePadAttack {
+    single_byte_attack: SingleByteAttack,
+}
+
+impl ManyTimePadAttack {
+    pub fn new() -> ManyTimePadAttack {
+        // Letter frequencies alone are unreliable on columns of a few bytes
+        let criterion = |bytes: &[u8]| english_frequency(bytes) + text_bytes(bytes);
+
+        ManyTimePadAttack {
+            single_byte_attack: SingleByteAttack::new().with_criterion(Box::new(criterion)),
+        }
+    }
+
+    pub fn with_single_byte_attack(mut self, attack: SingleByteAttack) -> ManyTimePadAttack {
+        self.single_byte_attack = attack;
+        self
+    }
+
+    // The recovered keystream, as long as the longest message, and the plaintexts
+    pub fn result<T: AsRef<[u8]>>(&self, ciphertexts: &[T]) -> ManyTimePadResult {
+        let length = ciphertexts.iter().map(|c| c.as_ref().len()).max().unwrap_or(0);
+
+        let (keystream, margins): (Vec<u8>, Vec<f32>) = (0..length)
+            .map(|position| {
+                let column: Vec<u8> = ciphertexts.iter().filter_map(|c| c.as_ref().get(position).cloned()).collect();
+                let result = self.single_byte_attack.result(&column);
+                (result.key, result.margin)
+            })
+            .unzip();
+
+        let plaintexts = ciphertexts.iter().map(|c| Xor::new(&keystream).decrypt(c.as_ref())).collect();
+
+        ManyTimePadResult { keystream, plaintexts, margins }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use evaluation::XorShift;
+    use super::*;
+
+    const PLAINTEXTS: [&'static str; 8] = [
+        "I have met them at close of day",
+        "Coming with vivid faces",
+        "From counter or desk among grey",
+        "Eighteenth-century houses.",
+        "I have passed with a nod of the head",
+        "Or polite meaningless words,",
+        "Or have lingered awhile and said",
+        "Polite meaningless words,",
+    ];
+
+    #[test]
+    fn recovers_reused_keystream() {
+        let keystream = XorShift::new(19).bytes(40);
+        let ciphertexts: Vec<Vec<u8>> = PLAINTEXTS.iter().map(|p| Xor::new(&keystream).encrypt(p.as_bytes())).collect();
+
+        let result = ManyTimePadAttack::new().result(&ciphertexts);
+        let (recovered, plaintexts) = (result.keystream, result.plaintexts);
+
+        assert_eq!(recovered.len(), 36);
+        assert_eq!(result.margins.len(), 36);
+        assert_eq!(plaintexts.len(), PLAINTEXTS.len());
+
+        // Columns of all the messages are mostly solved, the last ones only hold one byte
+        let correct = recovered.iter().zip(keystream.iter()).take(23).filter(|&(a, b)| a == b).count();
+        assert!(correct >= 20);
+        assert_eq!(&plaintexts[1][..5], b"Comin");
+        for (plaintext, ciphertext) in plaintexts.iter().zip(ciphertexts.iter()) {
+            assert_eq!(plaintext.len(), ciphertext.len());
+        }
+    }
+
+    #[test]
+    fn empty_input() {
+        let result = ManyTimePadAttack::new().result::<Vec<u8>>(&[]);
+        assert!(result.keystream.is_empty());
+        assert!(result.plaintexts.is_empty());
+        assert!(result.margins.is_empty());
+    }
+}
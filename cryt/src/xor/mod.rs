@@ -0,0 +1,750 @@
+use std::cmp::Ordering;
+
+use bytes;
+use criteria::{Criterion, text_bytes};
+use error::Result;
+use parallel::parallel_map;
+use pipeline::Codec;
+
+mod charset;
+mod crib;
+mod keysize;
+mod known;
+mod many_time;
+mod refine;
+
+pub use self::charset::Charset;
+pub use self::crib::{CribDrag, CribResult};
+pub use self::keysize::{kasiski_criterion, index_of_coincidence_criterion, autocorrelation_criterion};
+pub use self::known::{KnownPlaintextAttack, KnownPlaintextResult};
+pub use self::many_time::{ManyTimePadAttack, ManyTimePadResult};
+pub use self::refine::{KeyRefinement, RefinementResult};
+
+pub fn hamming_distance_criterion(input: &[u8], size: u32) -> f32 {
+    if size == 0 {
+        return 0.0;
+    }
+
+    let mut chunk_pairs_count = 0;
+    let mut distances_sum = 0;
+    for chunk_pair in input.chunks(size as usize).collect::<Vec<_>>().chunks(2) {
+        if chunk_pair.len() != 2 || chunk_pair[1].len() != size as usize {
+            break;
+        }
+
+        distances_sum += bytes::hamming_distance(chunk_pair[0], chunk_pair[1]);
+        chunk_pairs_count += 1;
+    }
+
+    // Input shorter than two chunks says nothing about the size
+    if chunk_pairs_count == 0 {
+        return 0.0;
+    }
+
+    1.0 / (distances_sum as f32 / chunk_pairs_count as f32 / size as f32)
+}
+
+pub struct Xor {
+    key: Vec<u8>
+}
+
+impl Xor {
+    // Builder methods
+    pub fn new(key: &[u8]) -> Xor {
+        Xor { key: key.to_vec() }
+    }
+
+    // Encryption methods
+    pub fn encrypt(&self, input: &[u8]) -> Vec<u8> {
+        input
+            .iter()
+            .zip(self.key.iter().cycle())
+            .map(|(b1, b2)| b1^b2)
+            .collect()
+    }
+
+    pub fn decrypt(&self, input: &[u8]) -> Vec<u8> {
+        self.encrypt(input)
+    }
+}
+
+impl Codec for Xor {
+    fn name(&self) -> String {
+        String::from("xor")
+    }
+
+    fn encode(&self, input: &[u8]) -> Result<Vec<u8>> {
+        Ok(self.encrypt(input))
+    }
+
+    fn decode(&self, input: &[u8]) -> Result<Vec<u8>> {
+        Ok(self.decrypt(input))
+    }
+}
+
+// Outcome of a single byte xor attack. The margin is the score of the key less
+// that of the second best, how much the key stands out from the others: it is
+// infinite when there is no other key. An empty charset leaves no key to try: the
+// key is then 0, with a NaN score and margin and no plaintext.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SingleByteResult {
+    pub key: u8,
+    pub score: f32,
+    pub plaintext: Vec<u8>,
+    pub margin: f32,
+    // Every key tried with its score, from the best
+    pub candidates: Vec<(u8, f32)>,
+}
+
+impl SingleByteResult {
+    fn new(mut candidates: Vec<(u8, f32, Vec<u8>)>) -> SingleByteResult {
+        if candidates.is_empty() {
+            return SingleByteResult {
+                key: 0,
+                score: f32::NAN,
+                plaintext: Vec::new(),
+                margin: f32::NAN,
+                candidates: Vec::new(),
+            };
+        }
+
+        let margin = match candidates.get(1) {
+            Some(&(_, second, _)) if !second.is_nan() => candidates[0].1 - second,
+            _ => f32::INFINITY,
+        };
+        let scores = candidates.iter().map(|&(key, score, _)| (key, score)).collect();
+        let (key, score, plaintext) = candidates.swap_remove(0);
+
+        SingleByteResult { key, score, plaintext, margin, candidates: scores }
+    }
+}
+
+pub struct SingleByteAttack {
+    criterion: Box<Criterion>,
+    charset: Charset,
+    threads: usize,
+}
+
+impl SingleByteAttack {
+
+    pub fn new() -> SingleByteAttack {
+        SingleByteAttack{
+            criterion: Box::new(text_bytes),
+            charset: Charset::All,
+            threads: 1,
+        }
+    }
+
+    pub fn with_criterion(mut self, bc: Box<Criterion>) -> SingleByteAttack {
+        self.criterion = bc;
+        self
+    }
+
+    // Bytes the key is looked for in
+    pub fn with_charset(mut self, charset: Charset) -> SingleByteAttack {
+        self.charset = charset;
+        self
+    }
+
+    // Number of threads the keys, or the inputs to detect, are shared between
+    pub fn with_threads(mut self, threads: usize) -> SingleByteAttack {
+        self.threads = threads;
+        self
+    }
+
+    // The best candidate, as ranked by candidates
+    pub fn result(&self, input: &[u8]) -> SingleByteResult {
+        SingleByteResult::new(self.candidates(input))
+    }
+
+    // All the keys of the charset with their score and decrypted result, from the
+    // best to the worst score. Keys with equal scores are ranked from the lowest to
+    // the highest, and NaN scores rank below any other.
+    pub fn candidates(&self, input: &[u8]) -> Vec<(u8, f32, Vec<u8>)> {
+        self.candidates_on(input, &self.charset, self.threads)
+    }
+
+    fn candidates_on(&self, input: &[u8], charset: &Charset, threads: usize) -> Vec<(u8, f32, Vec<u8>)> {
+        let keys = charset.keys();
+        let mut candidates = parallel_map(&keys, threads, |&key| {
+            let decrypted = Xor::new(&[key]).decrypt(input);
+            (key, self.criterion.score(&decrypted), decrypted)
+        });
+
+        candidates.sort_by(|&(k1, s1, _), &(k2, s2, _)| compare_scores(s1, s2).then(k1.cmp(&k2)));
+        candidates
+    }
+
+    // Attacks each of the inputs, e.g. the lines of a file of which one is
+    // encrypted, and ranks them by the score of their best candidate. Results hold
+    // the index of the input, ties rank the lower index first.
+    pub fn detect<T: AsRef<[u8]> + Sync>(&self, inputs: &[T]) -> Vec<(usize, SingleByteResult)> {
+        let indexed: Vec<(usize, &T)> = inputs.iter().enumerate().collect();
+
+        // The inputs are shared between the threads, each attacked on one
+        let mut results = parallel_map(&indexed, self.threads, |&(index, input)| {
+            (index, SingleByteResult::new(self.candidates_on(input.as_ref(), &self.charset, 1)))
+        });
+
+        results.sort_by(|&(i1, ref r1), &(i2, ref r2)| compare_scores(r1.score, r2.score).then(i1.cmp(&i2)));
+        results
+    }
+}
+
+// Orders scores from the highest to the lowest, with NaN last
+fn compare_scores(s1: f32, s2: f32) -> Ordering {
+    s2.partial_cmp(&s1).unwrap_or_else(|| s1.is_nan().cmp(&s2.is_nan()))
+}
+
+pub type KeysizeCriterion = Fn(&[u8], u32) -> f32 + Sync;
+
+// A keysize tried by a keysize attack, with its score
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeysizeResult {
+    pub keysize: u32,
+    pub score: f32,
+}
+
+pub struct KeysizeAttack {
+    criterion: Box<KeysizeCriterion>,
+    min_length: u32,
+    max_length: u32,
+    threads: usize,
+}
+
+impl KeysizeAttack {
+
+    pub fn new() -> KeysizeAttack {
+        KeysizeAttack {
+            criterion: Box::new(hamming_distance_criterion),
+            min_length: 1,
+            max_length: 32,
+            threads: 1,
+        }
+    }
+
+    pub fn with_criterion(mut self, criterion: Box<KeysizeCriterion>) -> KeysizeAttack {
+        self.criterion = criterion;
+        self
+    }
+
+    pub fn with_min_length(mut self, min_length: u32) -> KeysizeAttack {
+        self.min_length = min_length;
+        self
+    }
+
+    pub fn with_max_length(mut self, max_length: u32) -> KeysizeAttack {
+        self.max_length = max_length;
+        self
+    }
+
+    // Number of threads the sizes are shared between
+    pub fn with_threads(mut self, threads: usize) -> KeysizeAttack {
+        self.threads = threads;
+        self
+    }
+
+    // Every keysize from the best score
+    pub fn result(&self, input: &[u8]) -> Vec<KeysizeResult> {
+        let lengths: Vec<u32> = (self.min_length..self.max_length + 1).collect();
+        let mut results = parallel_map(&lengths, self.threads, |&keysize| {
+            KeysizeResult { keysize, score: (self.criterion)(input, keysize) }
+        });
+
+        // Equal scores keep the shorter size first
+        results.sort_by(|r1, r2| compare_scores(r1.score, r2.score));
+
+        results
+    }
+}
+
+// Outcome of a repeated xor attack: the key of the best scoring keysize, with the
+// margin of each of its bytes as solved by the single byte attack
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepeatedResult {
+    pub key: Vec<u8>,
+    pub plaintext: Vec<u8>,
+    pub score: f32,
+    pub keysize: u32,
+    pub margins: Vec<f32>,
+    // The key found for each keysize tried with the score of its plaintext, in the
+    // order of the keysize attack
+    pub candidates: Vec<(Vec<u8>, f32)>,
+}
+
+pub struct RepeatedAttack {
+    single_byte_attack: SingleByteAttack,
+    keysize_attack: KeysizeAttack,
+    result_criterion: Box<Criterion>,
+    keysizes_count: usize,
+    charset: Option<Charset>,
+    threads: usize,
+}
+
+impl RepeatedAttack {
+    pub fn new() -> RepeatedAttack {
+        RepeatedAttack {
+            single_byte_attack: SingleByteAttack::new(),
+            keysize_attack: KeysizeAttack::new(),
+            result_criterion: Box::new(text_bytes),
+            keysizes_count: 1,
+            charset: None,
+            threads: 1,
+        }
+    }
+
+    pub fn with_single_byte_attack(mut self, attack: SingleByteAttack) -> RepeatedAttack {
+        self.single_byte_attack = attack;
+        self
+    }
+
+    pub fn with_keysize_attack(mut self, attack: KeysizeAttack) -> RepeatedAttack {
+        self.keysize_attack = attack;
+        self
+    }
+
+    pub fn with_result_criterion(mut self, criterion: Box<Criterion>) -> RepeatedAttack {
+        self.result_criterion = criterion;
+        self
+    }
+
+    pub fn with_keysizes_count(mut self, keysizes: usize) -> RepeatedAttack {
+        self.keysizes_count = keysizes;
+        self
+    }
+
+    // Bytes the key is looked for in, in place of the charset of the single byte
+    // attack
+    pub fn with_charset(mut self, charset: Charset) -> RepeatedAttack {
+        self.charset = Some(charset);
+        self
+    }
+
+    // Number of threads the key columns of all the candidate keysizes are shared
+    // between. The single byte and keysize attacks have their own.
+    pub fn with_threads(mut self, threads: usize) -> RepeatedAttack {
+        self.threads = threads;
+        self
+    }
+
+    // The best result of the keysizes tried, which is empty with a score of minus
+    // infinity when no keysize is tried, as with an empty charset, or none scores
+    // above that
+    pub fn result(&self, input: &[u8]) -> RepeatedResult {
+        let charset = self.charset.as_ref().unwrap_or(&self.single_byte_attack.charset);
+        let keysizes_count = if charset.keys().is_empty() { 0 } else { self.keysizes_count };
+
+        let keysizes: Vec<u32> = self.keysize_attack.result(input)
+            .iter()
+            .take(keysizes_count)
+            .map(|result| result.keysize)
+            .collect();
+
+        // Each column of bytes xored with the same key byte is solved on its own
+        let columns: Vec<(u32, u32)> = keysizes.iter()
+            .flat_map(|&keysize| (0..keysize).map(move |nth_position| (keysize, nth_position)))
+            .collect();
+
+        let column_results = parallel_map(&columns, self.threads, |&(keysize, nth_position)| {
+            // Get the nth block of bytes separated by the keysize
+            let block: Vec<u8> = input
+                .iter()
+                .skip(nth_position as usize)
+                .step_by(keysize as usize)
+                .cloned()
+                .collect();
+
+            // Decrypt the block
+            let result = SingleByteResult::new(self.single_byte_attack
+                .candidates_on(&block, charset, self.single_byte_attack.threads));
+            (result.key, result.margin)
+        });
+
+        let mut final_result = RepeatedResult {
+            key: Vec::new(),
+            plaintext: Vec::new(),
+            score: f32::NEG_INFINITY,
+            keysize: 0,
+            margins: Vec::new(),
+            candidates: Vec::new(),
+        };
+
+        let mut column_results = column_results.into_iter();
+        for &keysize in keysizes.iter() {
+            let (key, margins): (Vec<u8>, Vec<f32>) = column_results.by_ref().take(keysize as usize).unzip();
+
+            // Keep track of the best result until now
+            let plaintext = Xor::new(&key).decrypt(input);
+            let score = self.result_criterion.score(&plaintext);
+            final_result.candidates.push((key.clone(), score));
+            if score > final_result.score {
+                final_result.key = key;
+                final_result.plaintext = plaintext;
+                final_result.score = score;
+                final_result.keysize = keysize;
+                final_result.margins = margins;
+            }
+        }
+
+        final_result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes;
+    use criteria::{english_frequency, make_common_byte};
+    use encoding::hex_decode;
+
+    use super::*;
+
+    #[test]
+    fn single_byte_against_single_byte() {
+        let key = &[0xd1];
+        let xorer = Xor::new(key);
+        let input = &[0x65];
+
+        assert_eq!(xorer.encrypt(input), vec![0xb4]);
+    }
+
+    #[test]
+    fn longer_bytes_against_shorter_bytes() {
+        let key = &[0xd1, 0x03];
+        let xorer = Xor::new(key);
+        let input = &[0x65, 0x21, 0xfa];
+
+        assert_eq!(xorer.encrypt(input), vec![0xb4, 0x22, 0x2b]);
+    }
+
+    #[test]
+    fn shorter_bytes_against_longer_bytes() {
+        let key = &[0x65, 0x21, 0xfa];
+        let xorer = Xor::new(key);
+        let input = &[0xd1, 0x03];
+
+        assert_eq!(xorer.encrypt(input), vec![0xb4, 0x22]);
+    }
+
+    #[test]
+    fn same_size_bytes() {
+        let key = &[0x65, 0x21, 0xfa];
+        let xorer = Xor::new(key);
+        let input = &[0xd1, 0x03, 0xbf];
+
+        assert_eq!(xorer.encrypt(input), vec![0xb4, 0x22, 0x45]);
+    }
+
+    #[test]
+    fn single_byte_decryption_retuns_byte_with_highest_score() {
+        let input = "eeeee English text with lots of 'e' eeeeeeee".as_bytes();
+        let key = &['x' as u8];
+        let encrypted = &(Xor::new(key)).encrypt(input);
+
+        let scorer = |i: &[u8]| {
+            i
+                .iter()
+                .filter(|&&b| b == 'e' as u8)
+                .collect::<Vec<_>>()
+                .len() as f32
+        };
+
+        let attack = SingleByteAttack::new()
+            .with_criterion(Box::new(scorer));
+
+        let result = attack.result(encrypted);
+
+        assert_eq!(result.key, 'x' as u8);
+        assert_eq!(result.score, 15.0);
+        assert_eq!(result.plaintext, input);
+    }
+
+    #[test]
+    fn single_byte_candidates_are_ranked() {
+        let encrypted = Xor::new(&[0x20]).encrypt(b"aab");
+        let candidates = SingleByteAttack::new()
+            .with_criterion(make_common_byte(b'a'))
+            .candidates(&encrypted);
+
+        assert_eq!(candidates.len(), 256);
+        assert_eq!(candidates[0], (0x20, 2.0 / 3.0, b"aab".to_vec()));
+        assert_eq!(candidates[1], (0x23, 1.0 / 3.0, b"bba".to_vec()));
+        assert_eq!((candidates[2].0, candidates[2].1), (0x00, 0.0));
+        assert_eq!((candidates[255].0, candidates[255].1), (0xff, 0.0));
+    }
+
+    #[test]
+    fn single_byte_ties_and_nan() {
+        let scorer = |i: &[u8]| match i[0] {
+            0x10 => f32::NAN,
+            0x05 | 0x07 => 1.0,
+            _ => 0.5,
+        };
+        let attack = SingleByteAttack::new().with_criterion(Box::new(scorer));
+        let keys: Vec<u8> = attack.candidates(&[0]).iter().map(|c| c.0).collect();
+
+        assert_eq!(&keys[..3], &[0x05, 0x07, 0x00]);
+        assert_eq!(keys[255], 0x10);
+        assert_eq!(attack.result(&[0]).key, 0x05);
+        assert_eq!(attack.result(&[0]).margin, 0.0);
+        assert_eq!(SingleByteAttack::new().with_criterion(Box::new(|_: &[u8]| -1.0)).result(b"x").key, 0x00);
+    }
+
+    #[test]
+    fn single_byte_with_charset() {
+        let attack = |charset| SingleByteAttack::new()
+            .with_criterion(Box::new(|i: &[u8]| i[0] as f32))
+            .with_charset(charset);
+
+        assert_eq!(attack(Charset::All).result(&[0]).key, 0xff);
+        assert_eq!(attack(Charset::Alphanumeric).result(&[0]).key, b'z');
+        assert_eq!(attack(Charset::HexDigits).result(&[0x01]).key, b'f');
+        assert_eq!(attack(Charset::Custom(vec![b'a'])).result(&[0]).margin, f32::INFINITY);
+        assert_eq!(attack(Charset::Printable).candidates(&[0]).len(), 95);
+        assert_eq!(attack(Charset::Custom(b"ab".to_vec())).candidates(&[0])[1], (b'a', 97.0, b"a".to_vec()));
+    }
+
+    #[test]
+    fn detect_encrypted_input() {
+        let inputs = vec![
+            b"\x8e\x13\x42\xf0\x07\x99\x5d\x2c\xee\x71\x03\xbb".to_vec(),
+            Xor::new(&[0x35]).encrypt(b"Now that the party is jumping"),
+            b"\x00\x01\x02".to_vec(),
+            Vec::new(),
+        ];
+
+        let results = SingleByteAttack::new()
+            .with_criterion(Box::new(english_frequency))
+            .detect(&inputs);
+
+        assert_eq!(results.len(), 4);
+        assert_eq!((results[0].0, results[0].1.key), (1, 0x35));
+        assert_eq!(results[0].1.plaintext, b"Now that the party is jumping".to_vec());
+        assert_eq!(results[3].0, 3);
+    }
+
+    #[test]
+    fn single_byte_attack_with_english_frequency() {
+        let encrypted = hex_decode("1b37373331363f78151b7f2b783431333d78397828372d363c78373e783a393b3736").unwrap();
+
+        let result = SingleByteAttack::new()
+            .with_criterion(Box::new(english_frequency))
+            .result(&encrypted);
+
+        assert_eq!(result.key, 'X' as u8);
+        assert_eq!(result.plaintext, "Cooking MC's like a pound of bacon".as_bytes());
+        assert_eq!(result.candidates.len(), 256);
+        assert_eq!(result.candidates[0], (result.key, result.score));
+        assert_eq!(result.margin, result.score - result.candidates[1].1);
+        assert!(result.margin > 0.0);
+    }
+
+    #[test]
+    fn test_repeated_xor_keysize() {
+        let keysize_scorer = |_: &[u8], keysize| {
+            if keysize == 8 {
+                2.0
+            } else {
+                1.0 / keysize as f32
+            }
+        };
+
+        let result = KeysizeAttack::new()
+            .with_min_length(1)
+            .with_max_length(10)
+            .with_criterion(Box::new(keysize_scorer))
+            .result("test".as_bytes());
+
+        assert_eq!(
+            result.iter().map(|r| (r.keysize, r.score)).collect::<Vec<_>>(),
+            [(8, 2.0), (1, 1.0/1.0), (2, 1.0/2.0), (3, 1.0/3.0), (4, 1.0/4.0), (5, 1.0/5.0), (6, 1.0/6.0), (7, 1.0/7.0), (9, 1.0/9.0), (10, 1.0/10.0)]
+        )
+    }
+
+    #[test]
+    fn hamming_distance_criterion_with_input_multiple_of_size() {
+        let input = Vec::from("some random text in eng!".as_bytes());
+        let score = hamming_distance_criterion(&input, 6);
+
+        let expected = 1.0 / (((
+            bytes::hamming_distance("some r".as_bytes(), "andom ".as_bytes()) +
+            bytes::hamming_distance("text i".as_bytes(), "n eng!".as_bytes())
+        ) as f32 / 2.0) / 6.0);
+
+        assert_eq!(score, expected)
+    }
+
+    #[test]
+    fn hamming_distance_criterion_with_input_not_multiple_of_size() {
+        let input = Vec::from("some random text!!XX".as_bytes());
+        let score = hamming_distance_criterion(&input, 3);
+
+        let expected = 1.0 / (((
+            bytes::hamming_distance("som".as_bytes(), "e r".as_bytes()) +
+            bytes::hamming_distance("and".as_bytes(), "om ".as_bytes()) +
+            bytes::hamming_distance("tex".as_bytes(), "t!!".as_bytes())
+        ) as f32 / 3.0) / 3.0);
+
+        assert_eq!(score, expected)
+    }
+
+    #[test]
+    fn hamming_distance_criterion_with_input_pairs_not_multiple_of_2() {
+        let input = Vec::from("some random tex!".as_bytes());
+        let score = hamming_distance_criterion(&input, 3);
+
+        let expected = 1.0 / (((
+            bytes::hamming_distance("som".as_bytes(), "e r".as_bytes()) +
+            bytes::hamming_distance("and".as_bytes(), "om ".as_bytes())
+        ) as f32 / 2.0) / 3.0);
+
+        assert_eq!(score, expected)
+    }
+
+    #[test]
+    fn hamming_distance_criterion_with_input_shorter_than_two_chunks() {
+        assert_eq!(hamming_distance_criterion(b"some", 3), 0.0);
+        assert_eq!(hamming_distance_criterion(b"", 1), 0.0);
+        assert_eq!(hamming_distance_criterion(b"some random text", 0), 0.0);
+    }
+
+    #[test]
+    fn keysize_attack_with_nan_scores() {
+        let scorer = |_: &[u8], keysize| if keysize == 3 { f32::NAN } else { 1.0 };
+        let result = KeysizeAttack::new()
+            .with_max_length(4)
+            .with_criterion(Box::new(scorer))
+            .result(b"test");
+
+        assert_eq!(result.iter().map(|r| r.keysize).collect::<Vec<_>>(), [1, 2, 4, 3]);
+    }
+
+    #[test]
+    fn repeated_xor_decrypted() {
+        let plain_text = "this text is encrypted with repeated xor".as_bytes();
+        let key = "SeCreT".as_bytes();
+        let input = Xor::new(key).encrypt(plain_text);
+
+        let keysize_scorer = move |_: &[u8], keysize| {
+            if keysize == key.len() as u32 {
+                2.0
+            } else {
+                1.0 / keysize as f32
+            }
+        };
+
+        let xor_scorer = |input: &[u8]| {
+            input
+                .iter()
+                .filter(|&&b| "this text is encrypted with repeated xor"
+                            .as_bytes()
+                            .iter()
+                            .any(|&c| c == b))
+                .count() as f32
+        };
+
+        let result = RepeatedAttack::new()
+            .with_single_byte_attack(SingleByteAttack::new()
+                                     .with_criterion(Box::new(xor_scorer)))
+            .with_keysize_attack(KeysizeAttack::new()
+                                 .with_min_length(1)
+                                 .with_max_length(15)
+                                 .with_criterion(Box::new(keysize_scorer)))
+            .result(&input);
+
+        assert_eq!(Vec::from(key), result.key);
+        assert_eq!(Vec::from(plain_text), result.plaintext);
+    }
+
+    #[test]
+    fn repeated_result_details() {
+        let plain_text = b"Every byte of the key comes with the margin by which its column was solved.";
+        let input = Xor::new(b"ICE").encrypt(plain_text);
+
+        let result = RepeatedAttack::new()
+            .with_single_byte_attack(SingleByteAttack::new().with_criterion(Box::new(english_frequency)))
+            .with_keysize_attack(KeysizeAttack::new()
+                                 .with_max_length(6)
+                                 .with_criterion(Box::new(|_: &[u8], keysize| if keysize == 3 { 1.0 } else { 0.0 })))
+            .with_keysizes_count(2)
+            .result(&input);
+
+        assert_eq!(result.key, b"ICE".to_vec());
+        assert_eq!(result.keysize, 3);
+        assert_eq!(result.score, 1.0);
+        assert_eq!(result.margins.len(), 3);
+        assert!(result.margins.iter().all(|&m| m > 0.0));
+        assert_eq!(result.candidates.len(), 2);
+        assert_eq!(result.candidates[0], (b"ICE".to_vec(), 1.0));
+        assert_eq!(result.candidates[1].0.len(), 1);
+
+        let none = RepeatedAttack::new().with_keysizes_count(0).result(&input);
+        assert_eq!((none.key.len(), none.keysize, none.score), (0, 0, f32::NEG_INFINITY));
+        assert!(none.candidates.is_empty());
+    }
+
+    #[test]
+    fn repeated_xor_with_charset() {
+        let plain_text = b"Keys made of letters leave fewer candidates to the attack of each column.";
+        let input = Xor::new(b"pass").encrypt(plain_text);
+        let attack = RepeatedAttack::new()
+            .with_single_byte_attack(SingleByteAttack::new()
+                                     .with_criterion(Box::new(english_frequency))
+                                     .with_charset(Charset::HexDigits))
+            .with_keysize_attack(KeysizeAttack::new().with_min_length(4).with_max_length(4));
+
+        // The charset of the repeated attack replaces that of the single byte attack
+        assert!(attack.result(&input).key.iter().all(|b| b.is_ascii_hexdigit()));
+
+        let result = attack.with_charset(Charset::Custom(b"aps".to_vec())).result(&input);
+        assert_eq!(result.key, b"pass".to_vec());
+        assert_eq!(result.plaintext, plain_text.to_vec());
+    }
+
+    #[test]
+    fn results_do_not_depend_on_threads() {
+        let plain_text = b"Threads share the keys, the sizes and the columns of the key, \
+                           but their results are merged in the same order whatever their count.";
+        let input = Xor::new(b"Shared").encrypt(plain_text);
+        let lines = vec![input[..20].to_vec(), Xor::new(b"k").encrypt(plain_text), input[20..].to_vec()];
+
+        let single_byte = |threads| SingleByteAttack::new()
+            .with_criterion(Box::new(english_frequency))
+            .with_threads(threads);
+        let keysize = |threads| KeysizeAttack::new().with_max_length(12).with_threads(threads);
+        let repeated = |threads| RepeatedAttack::new()
+            .with_single_byte_attack(single_byte(threads))
+            .with_keysize_attack(keysize(threads))
+            .with_keysizes_count(4)
+            .with_threads(threads);
+
+        for &threads in [2, 3, 8].iter() {
+            assert_eq!(single_byte(threads).candidates(&input[..30]), single_byte(1).candidates(&input[..30]));
+            assert_eq!(single_byte(threads).detect(&lines), single_byte(1).detect(&lines));
+            assert_eq!(keysize(threads).result(&input), keysize(1).result(&input));
+            assert_eq!(repeated(threads).result(&input), repeated(1).result(&input));
+        }
+        assert_eq!(repeated(4).result(&input).keysize, 6);
+    }
+
+    #[test]
+    fn empty_charset_finds_no_key() {
+        let input = Xor::new(b"key").encrypt(b"attack at dawn, attack at dawn");
+        let attack = SingleByteAttack::new().with_charset(Charset::Custom(Vec::new()));
+
+        let result = attack.result(&input);
+        assert_eq!((result.key, result.plaintext.len(), result.candidates.len()), (0, 0, 0));
+        assert!(result.score.is_nan() && result.margin.is_nan());
+
+        let detected = attack.detect(&[&input[..10], &input[10..]]);
+        assert_eq!(detected.iter().map(|&(index, _)| index).collect::<Vec<_>>(), [0, 1]);
+        assert!(detected.iter().all(|&(_, ref result)| result.score.is_nan()));
+
+        let repeated = RepeatedAttack::new().with_charset(Charset::Custom(Vec::new())).result(&input);
+        assert_eq!((repeated.key.len(), repeated.keysize, repeated.score), (0, 0, f32::NEG_INFINITY));
+        assert!(repeated.candidates.is_empty());
+
+        let many_time = ManyTimePadAttack::new().with_single_byte_attack(attack).result(&[&input[..10], &input[10..]]);
+        assert_eq!(many_time.keystream, vec![0; 20]);
+        assert!(many_time.margins.iter().all(|m| m.is_nan()));
+    }
+}
@@ -0,0 +1,156 @@
+use criteria::{Criterion, Dictionary, english_frequency, text_bytes};
+use parallel::parallel_map;
+use super::{Charset, Xor};
+
+// Outcome of a key refinement: the refined key with its plaintext and score, and
+// the positions of the bytes that differ from the given key
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefinementResult {
+    pub key: Vec<u8>,
+    pub plaintext: Vec<u8>,
+    pub score: f32,
+    pub positions: Vec<usize>,
+}
+
+// Improves a repeated xor key found column by column, as by RepeatedAttack, which
+// can miss a few bytes when the columns are short. Each byte of the key is in turn
+// replaced by the one of the charset that gives the best score to the whole
+// plaintext, until a round over the key changes nothing.
+pub struct KeyRefinement {
+    criterion: Box<Criterion>,
+    charset: Charset,
+    max_rounds: usize,
+    threads: usize,
+}
+
+impl KeyRefinement {
+    pub fn new() -> KeyRefinement {
+        // Letter frequencies alone barely change with a single byte of the key,
+        // while words appear when it is right
+        let dictionary = Dictionary::builtin();
+        let criterion = move |bytes: &[u8]| dictionary.score(bytes) + english_frequency(bytes) + text_bytes(bytes);
+
+        KeyRefinement {
+            criterion: Box::new(criterion),
+            charset: Charset::All,
+            max_rounds: 16,
+            threads: 1,
+        }
+    }
+
+    pub fn with_criterion(mut self, criterion: Box<Criterion>) -> KeyRefinement {
+        self.criterion = criterion;
+        self
+    }
+
+    pub fn with_charset(mut self, charset: Charset) -> KeyRefinement {
+        self.charset = charset;
+        self
+    }
+
+    pub fn with_max_rounds(mut self, max_rounds: usize) -> KeyRefinement {
+        self.max_rounds = max_rounds;
+        self
+    }
+
+    // Number of threads the candidate bytes are shared between
+    pub fn with_threads(mut self, threads: usize) -> KeyRefinement {
+        self.threads = threads;
+        self
+    }
+
+    // A byte is only replaced by one scoring strictly better, the lowest of equals
+    pub fn refine(&self, input: &[u8], key: &[u8]) -> RefinementResult {
+        let mut refined = key.to_vec();
+        let candidates = self.charset.keys();
+        let mut score = self.criterion.score(&Xor::new(&refined).decrypt(input));
+
+        for _ in 0..self.max_rounds {
+            let mut changed = false;
+
+            for position in 0..refined.len() {
+                let scores = parallel_map(&candidates, self.threads, |&byte| {
+                    let mut key = refined.clone();
+                    key[position] = byte;
+                    self.criterion.score(&Xor::new(&key).decrypt(input))
+                });
+
+                for (&byte, &new_score) in candidates.iter().zip(scores.iter()) {
+                    if new_score > score {
+                        score = new_score;
+                        refined[position] = byte;
+                        changed = true;
+                    }
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        let positions = (0..key.len()).filter(|&i| key[i] != refined[i]).collect();
+        let plaintext = Xor::new(&refined).decrypt(input);
+        RefinementResult { key: refined, plaintext, score, positions }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xor::{KeysizeAttack, RepeatedAttack, SingleByteAttack};
+    use super::*;
+
+    const PLAINTEXT: &'static [u8] = b"\
+The quick brown fox jumps over the lazy dog while the small cat sleeps on the warm mat.";
+
+    const LONGER_PLAINTEXT: &'static [u8] = b"\
+It is a truth universally acknowledged, that a single man in possession of a good fortune, must be in want";
+
+    #[test]
+    fn fixes_wrong_key_bytes() {
+        let input = Xor::new(b"Sp3cTr").encrypt(PLAINTEXT);
+        let result = KeyRefinement::new().refine(&input, b"Sp\x00cTX");
+
+        assert_eq!(result.key, b"Sp3cTr".to_vec());
+        assert_eq!(result.plaintext, PLAINTEXT.to_vec());
+        assert_eq!(result.positions, [2, 5]);
+
+        let unchanged = KeyRefinement::new().refine(&input, b"Sp3cTr");
+        assert_eq!((unchanged.key, unchanged.positions), (b"Sp3cTr".to_vec(), Vec::new()));
+        assert_eq!(unchanged.score, result.score);
+    }
+
+    #[test]
+    fn refines_repeated_attack_key() {
+        let input = Xor::new(b"Hidden key").encrypt(LONGER_PLAINTEXT);
+        let key = RepeatedAttack::new()
+            .with_single_byte_attack(SingleByteAttack::new().with_criterion(Box::new(english_frequency)))
+            .with_keysize_attack(KeysizeAttack::new().with_min_length(10).with_max_length(10))
+            .result(&input)
+            .key;
+
+        let refinement = KeyRefinement::new().with_charset(Charset::Printable).with_threads(3);
+        let result = refinement.refine(&input, &key);
+
+        // Columns of about ten bytes leave three wrong key bytes
+        assert_eq!(key, b"Iidden j,y".to_vec());
+        assert_eq!(result.key, b"Hidden key".to_vec());
+        assert_eq!(result.positions, [0, 7, 8]);
+    }
+
+    #[test]
+    fn charset_and_rounds_limit_changes() {
+        let input = Xor::new(b"ab").encrypt(PLAINTEXT);
+
+        let result = KeyRefinement::new().with_max_rounds(0).refine(&input, b"xy");
+        assert_eq!((result.key, result.positions), (b"xy".to_vec(), Vec::new()));
+        let result = KeyRefinement::new().refine(&input, b"");
+        assert_eq!((result.key, result.plaintext, result.positions), (Vec::new(), Vec::new(), Vec::new()));
+
+        // The right first byte is not in the charset
+        let result = KeyRefinement::new().with_charset(Charset::Custom(b"bx".to_vec())).refine(&input, b"xy");
+        assert!(b"bx".contains(&result.key[0]));
+        assert_eq!(result.key[1], b'b');
+        assert!(result.positions.contains(&1));
+    }
+}